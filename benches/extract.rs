@@ -0,0 +1,63 @@
+use criterion::{criterion_group, criterion_main, Criterion};
+use treesitter::ASTConversionService;
+
+// A few thousand lines of generated but realistic Rust (docs, attributes,
+// generics, impls, calls, nested control flow) so per-node costs dominate.
+// Generated rather than read from the tree so the input stays fixed as the
+// sources change.
+fn large_source() -> String {
+    let mut code = String::from("//! Benchmark input\nuse std::collections::{HashMap, HashSet};\n");
+    for i in 0..200 {
+        code.push_str(&format!(
+            r#"
+/// Item number {i}
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct Item{i}<T: Clone> where T: Default {{
+    pub id: u64,
+    name: String,
+    values: Vec<T>,
+}}
+
+impl<T: Clone + Default> Item{i}<T> {{
+    /// Build an empty item
+    pub fn new(id: u64) -> Self {{
+        Item{i} {{ id, name: format!("item-{i}"), values: Vec::new() }}
+    }}
+    fn total(&self, scale: u64) -> u64 {{
+        let mut sum = 0;
+        for (index, _value) in self.values.iter().enumerate() {{
+            if index % 2 == 0 {{
+                sum += scale * index as u64;
+            }} else {{
+                sum = sum.saturating_sub(1);
+            }}
+        }}
+        let lookup: HashMap<u64, String> = HashMap::new();
+        lookup.get(&self.id).map(|s| s.len() as u64).unwrap_or(sum)
+    }}
+}}
+
+pub enum State{i} {{ Idle, Running(u32), Done {{ code: i32 }} }}
+
+const LIMIT_{i}: usize = {i};
+"#
+        ));
+    }
+    code
+}
+
+fn extract(c: &mut Criterion) {
+    let code = large_source();
+    let service = ASTConversionService::new(code.clone()).unwrap();
+
+    c.bench_function("parse", |b| {
+        b.iter(|| ASTConversionService::new(code.clone()).unwrap())
+    });
+    c.bench_function("generate_json", |b| b.iter(|| service.generate_json()));
+    c.bench_function("generate_ast_with_relations", |b| {
+        b.iter(|| service.generate_ast_with_relations())
+    });
+}
+
+criterion_group!(benches, extract);
+criterion_main!(benches);
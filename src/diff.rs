@@ -0,0 +1,123 @@
+use serde_json::{json, Map, Value};
+
+// Categories whose items are named top-level declarations
+const DIFFED_CATEGORIES: &[&str] = &[
+    "imports",
+    "functions",
+    "structs",
+    "enums",
+    "traits",
+    "type_aliases",
+    "constants",
+    "globals",
+    "modules_and_impls",
+];
+
+// Keys that don't change what an item declares: where it sits in the file,
+// its docs, and what a function body does
+const IGNORED_KEYS: &[&str] = &[
+    "start_line",
+    "start_col",
+    "end_line",
+    "end_col",
+    "start_byte",
+    "end_byte",
+    "docs",
+    "body",
+    "called_methods",
+    "method_chains",
+    "local_variables",
+];
+
+/// Compare two `generate_json` documents item by item, matched by name
+/// within each category, as `{ added, removed, changed }` lists of
+/// `{ category, name }`. A function or method changes when its `signature`
+/// does (reported as `before`/`after`); any other item when what it
+/// declares does, ignoring positions, docs and bodies.
+pub fn diff(before: &Value, after: &Value) -> Value {
+    let mut added = Vec::new();
+    let mut removed = Vec::new();
+    let mut changed = Vec::new();
+    for category in DIFFED_CATEGORIES {
+        let old_items = named_items(before, category);
+        let new_items = named_items(after, category);
+        for (name, new) in &new_items {
+            match old_items.iter().find(|(old_name, _)| old_name == name) {
+                None => added.push(json!({ "category": category, "name": name })),
+                Some((_, old)) if comparable(old) != comparable(new) => {
+                    let mut entry = json!({ "category": category, "name": name });
+                    if let (Some(old), Some(new)) = (signatures(old), signatures(new)) {
+                        entry["before"] = old;
+                        entry["after"] = new;
+                    }
+                    changed.push(entry);
+                }
+                Some(_) => {}
+            }
+        }
+        for (name, _) in &old_items {
+            if !new_items.iter().any(|(new_name, _)| new_name == name) {
+                removed.push(json!({ "category": category, "name": name }));
+            }
+        }
+    }
+    json!({
+        "added": added,
+        "removed": removed,
+        "changed": changed,
+    })
+}
+
+// The category's items grouped by name in order of first appearance, since
+// a name can repeat (several `impl` blocks for one type)
+fn named_items<'v>(document: &'v Value, category: &str) -> Vec<(&'v str, Vec<&'v Value>)> {
+    let mut grouped: Vec<(&str, Vec<&Value>)> = Vec::new();
+    let items = document[category]
+        .as_array()
+        .map(Vec::as_slice)
+        .unwrap_or(&[]);
+    for item in items {
+        let Some(name) = item["name"].as_str() else {
+            continue;
+        };
+        match grouped.iter_mut().find(|(existing, _)| *existing == name) {
+            Some((_, group)) => group.push(item),
+            None => grouped.push((name, vec![item])),
+        }
+    }
+    grouped
+}
+
+// What two versions of an item are compared by: their signatures when they
+// have one, otherwise the item without `IGNORED_KEYS`
+fn comparable(items: &[&Value]) -> Vec<Value> {
+    items
+        .iter()
+        .map(|item| match item.get("signature") {
+            Some(signature) => signature.clone(),
+            None => without_ignored_keys(item),
+        })
+        .collect()
+}
+
+fn signatures(items: &[&Value]) -> Option<Value> {
+    let signatures: Option<Vec<&Value>> = items.iter().map(|item| item.get("signature")).collect();
+    match signatures?.as_slice() {
+        [signature] => Some((*signature).clone()),
+        signatures => Some(json!(signatures)),
+    }
+}
+
+fn without_ignored_keys(value: &Value) -> Value {
+    match value {
+        Value::Object(object) => Value::Object(
+            object
+                .iter()
+                .filter(|(key, _)| !IGNORED_KEYS.contains(&key.as_str()))
+                .map(|(key, value)| (key.clone(), without_ignored_keys(value)))
+                .collect::<Map<_, _>>(),
+        ),
+        Value::Array(items) => Value::Array(items.iter().map(without_ignored_keys).collect()),
+        _ => value.clone(),
+    }
+}
@@ -0,0 +1,49 @@
+use std::fmt;
+use std::time::Duration;
+
+use tree_sitter::{LanguageError, QueryError};
+
+/// Errors raised while setting up the parser or parsing the source.
+#[derive(Debug)]
+pub enum ConversionError {
+    /// The language grammar could not be loaded into the parser
+    GrammarLoad(LanguageError),
+    /// tree-sitter gave up on the input without producing a tree
+    ParseFailed { input_len: usize },
+    /// Parsing took longer than the timeout the service was created with
+    Timeout { input_len: usize, timeout: Duration },
+    /// A tree-sitter query passed to `query` didn't compile
+    Query(QueryError),
+}
+
+impl fmt::Display for ConversionError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ConversionError::GrammarLoad(err) => write!(f, "error loading grammar: {}", err),
+            ConversionError::ParseFailed { input_len } => {
+                write!(f, "failed to parse code ({} bytes)", input_len)
+            }
+            ConversionError::Timeout { input_len, timeout } => write!(
+                f,
+                "parsing timed out after {} ms ({} bytes)",
+                timeout.as_millis(),
+                input_len
+            ),
+            ConversionError::Query(err) => write!(f, "invalid query: {}", err),
+        }
+    }
+}
+
+impl std::error::Error for ConversionError {}
+
+impl From<LanguageError> for ConversionError {
+    fn from(err: LanguageError) -> Self {
+        ConversionError::GrammarLoad(err)
+    }
+}
+
+impl From<QueryError> for ConversionError {
+    fn from(err: QueryError) -> Self {
+        ConversionError::Query(err)
+    }
+}
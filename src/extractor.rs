@@ -0,0 +1,124 @@
+use serde_json::{json, Value};
+use tree_sitter::Node;
+
+use crate::service::ASTConversionService;
+
+/// One top-level category of `generate_json`. Register your own with
+/// [`ASTConversionService::add_extractor`] to add a key to the output
+/// without changing this crate; the built-in categories are extractors too.
+pub trait Extractor {
+    /// The key the category is emitted under. Registering one with a
+    /// built-in category's key replaces that category.
+    fn key(&self) -> &str;
+    /// The category for the file whose syntax tree is rooted at `root`;
+    /// `service.node_text` gives any node's source.
+    fn extract(&self, service: &ASTConversionService, root: Node) -> Value;
+}
+
+// A built-in category, extracted by its accessor on the service
+pub(crate) struct Builtin {
+    key: &'static str,
+    extract: fn(&ASTConversionService) -> Value,
+}
+
+impl Extractor for Builtin {
+    fn key(&self) -> &str {
+        self.key
+    }
+    fn extract(&self, service: &ASTConversionService, _root: Node) -> Value {
+        (self.extract)(service)
+    }
+}
+
+// In the order of `CATEGORIES`
+pub(crate) const BUILTINS: &[Builtin] = &[
+    Builtin {
+        key: "docs",
+        extract: |service| json!(service.docs()),
+    },
+    Builtin {
+        key: "imports",
+        extract: |service| json!(service.imports()),
+    },
+    Builtin {
+        key: "functions",
+        extract: |service| json!(service.functions()),
+    },
+    Builtin {
+        key: "structs",
+        extract: |service| json!(service.structs()),
+    },
+    Builtin {
+        key: "enums",
+        extract: |service| json!(service.enums()),
+    },
+    Builtin {
+        key: "traits",
+        extract: |service| json!(service.traits()),
+    },
+    Builtin {
+        key: "type_aliases",
+        extract: |service| json!(service.type_aliases()),
+    },
+    Builtin {
+        key: "relations",
+        extract: |service| json!(service.relations()),
+    },
+    Builtin {
+        key: "edges",
+        extract: |service| json!(service.edges()),
+    },
+    Builtin {
+        key: "call_graph",
+        extract: |service| {
+            let edges = service.call_edges().into_iter();
+            json!(edges
+                .map(|(from, to)| json!({ "from": from, "to": to }))
+                .collect::<Vec<_>>())
+        },
+    },
+    Builtin {
+        key: "constants",
+        extract: |service| json!(service.constants()),
+    },
+    Builtin {
+        key: "macros",
+        extract: |service| json!(service.macros()),
+    },
+    Builtin {
+        key: "modules_and_impls",
+        extract: |service| json!(service.modules_and_impls()),
+    },
+    Builtin {
+        key: "metadata",
+        extract: |service| service.metadata(),
+    },
+    Builtin {
+        key: "errors",
+        extract: |service| json!(service.errors()),
+    },
+    Builtin {
+        key: "nested_items",
+        extract: |service| json!(service.nested_items()),
+    },
+    Builtin {
+        key: "globals",
+        extract: |service| json!(service.globals()),
+    },
+    Builtin {
+        key: "schemas",
+        extract: |service| json!(service.schemas()),
+    },
+    Builtin {
+        key: "literals",
+        extract: |service| json!(service.literals()),
+    },
+    Builtin {
+        key: "tests",
+        extract: |service| json!(service.tests()),
+    },
+    Builtin {
+        key: "foreign_items",
+        extract: |service| json!(service.foreign_items()),
+    },
+];
@@ -0,0 +1,302 @@
+use std::fmt;
+use std::str::FromStr;
+
+use crate::model::ExitKind;
+use crate::tree::Kind;
+
+/// Source languages the extractor can parse.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Language {
+    #[default]
+    Rust,
+    JavaScript,
+    Python,
+}
+
+// Node kinds that make up each extracted category in one grammar. The
+// extractors only ever test kinds through this table, so a category a
+// language doesn't have is simply an empty list.
+pub(crate) struct NodeKinds {
+    pub imports: &'static [&'static str],
+    pub functions: &'static [&'static str],
+    pub structs: &'static [&'static str],
+    pub enums: &'static [&'static str],
+    pub traits: &'static [&'static str],
+    pub type_aliases: &'static [&'static str],
+    pub impls: &'static [&'static str],
+    pub constants: &'static [&'static str],
+    pub globals: &'static [&'static str],
+    pub modules: &'static [&'static str],
+    // `extern "ABI" { ... }` blocks of foreign declarations
+    pub foreign_modules: &'static [&'static str],
+    pub calls: &'static [&'static str],
+    // Callee kinds that make a call a method call (`recv.method()`)
+    pub member_accesses: &'static [&'static str],
+    pub comments: &'static [&'static str],
+    // Literal kinds, each paired with the `type` reported for it
+    pub literals: &'static [(&'static str, &'static str)],
+    // Branching and looping kinds, each paired with the `control_flow` key
+    // it's counted under
+    pub control_flow: &'static [(&'static str, &'static str)],
+    // Kinds that leave the function early or jump out of a loop
+    pub exits: &'static [(&'static str, ExitKind)],
+    // Kinds whose body a `return` (or `?`) leaves instead of the enclosing
+    // function: closures, and async blocks
+    pub closures: &'static [&'static str],
+    // Parameter kinds that keep their name and default value under fields,
+    // as (kind, name field, default field); with no name field the first
+    // named child is the name (`*args`, `x: int`). Any other parameter is
+    // its own name.
+    pub parameter_fields: &'static [(&'static str, Option<&'static str>, Option<&'static str>)],
+    // Markers in a parameter list that aren't parameters (Python's bare `*`)
+    pub parameter_separators: &'static [&'static str],
+    // The `--mode tree` kind of each node kind that has one; every other
+    // node is `Kind::Undefined`
+    pub tree: &'static [(&'static str, Kind)],
+}
+
+const RUST_KINDS: NodeKinds = NodeKinds {
+    imports: &["use_declaration"],
+    functions: &["function_item"],
+    structs: &["struct_item"],
+    enums: &["enum_item"],
+    traits: &["trait_item"],
+    type_aliases: &["type_item"],
+    impls: &["impl_item"],
+    constants: &["const_item"],
+    globals: &["static_item"],
+    modules: &["mod_item"],
+    foreign_modules: &["foreign_mod_item"],
+    calls: &["call_expression"],
+    member_accesses: &["field_expression"],
+    comments: &["line_comment", "block_comment"],
+    literals: &[
+        ("string_literal", "string"),
+        ("raw_string_literal", "string"),
+        ("integer_literal", "integer"),
+        ("float_literal", "float"),
+        ("boolean_literal", "boolean"),
+    ],
+    control_flow: &[
+        ("if_expression", "if"),
+        ("match_expression", "match"),
+        ("for_expression", "for"),
+        ("while_expression", "while"),
+        ("loop_expression", "loop"),
+    ],
+    exits: &[
+        ("return_expression", ExitKind::Return),
+        ("break_expression", ExitKind::Break),
+        ("continue_expression", ExitKind::Continue),
+        ("try_expression", ExitKind::Try),
+    ],
+    closures: &["closure_expression", "async_block"],
+    parameter_fields: &[("parameter", Some("pattern"), None)],
+    parameter_separators: &[],
+    tree: &[
+        ("source_file", Kind::Root),
+        ("line_comment", Kind::Comment),
+        ("block_comment", Kind::Comment),
+        ("import", Kind::Import),
+        ("mod_item", Kind::Module),
+        ("struct_item", Kind::Struct),
+        ("enum_item", Kind::Enum),
+        ("attribute_item", Kind::Derive),
+        ("function_item", Kind::Function),
+        ("impl_item", Kind::Impl),
+        ("field_declaration", Kind::Field),
+        ("enum_variant", Kind::Variant),
+        ("let_declaration", Kind::Variable),
+        ("type_item", Kind::Type),
+        ("trait_item", Kind::Trait),
+        ("if_expression", Kind::If),
+        ("else_clause", Kind::Else),
+        ("loop_expression", Kind::Loop),
+        ("tuple_expression", Kind::Tuple),
+        ("array_expression", Kind::Array),
+        ("call_expression", Kind::FunctionCall),
+    ],
+};
+
+const JAVASCRIPT_KINDS: NodeKinds = NodeKinds {
+    imports: &["import_statement"],
+    functions: &["function_declaration", "generator_function_declaration"],
+    structs: &["class_declaration"],
+    enums: &[],
+    traits: &[],
+    type_aliases: &[],
+    impls: &[],
+    constants: &[],
+    globals: &[],
+    modules: &[],
+    foreign_modules: &[],
+    calls: &["call_expression"],
+    member_accesses: &["member_expression"],
+    comments: &["comment"],
+    literals: &[
+        ("string", "string"),
+        ("number", "number"),
+        ("true", "boolean"),
+        ("false", "boolean"),
+    ],
+    control_flow: &[
+        ("if_statement", "if"),
+        ("switch_statement", "match"),
+        ("for_statement", "for"),
+        ("for_in_statement", "for"),
+        ("while_statement", "while"),
+        ("do_statement", "loop"),
+    ],
+    exits: &[
+        ("return_statement", ExitKind::Return),
+        ("break_statement", ExitKind::Break),
+        ("continue_statement", ExitKind::Continue),
+    ],
+    closures: &[
+        "arrow_function",
+        "function_expression",
+        "generator_function",
+    ],
+    parameter_fields: &[
+        ("assignment_pattern", Some("left"), Some("right")),
+        ("rest_pattern", None, None),
+    ],
+    parameter_separators: &[],
+    tree: &[
+        ("program", Kind::Root),
+        ("comment", Kind::Comment),
+        ("import_statement", Kind::Import),
+        ("class_declaration", Kind::Struct),
+        ("function_declaration", Kind::Function),
+        ("generator_function_declaration", Kind::Function),
+        ("method_definition", Kind::Function),
+        ("field_definition", Kind::Field),
+        ("lexical_declaration", Kind::Variable),
+        ("variable_declaration", Kind::Variable),
+        ("if_statement", Kind::If),
+        ("else_clause", Kind::Else),
+        ("for_statement", Kind::Loop),
+        ("for_in_statement", Kind::Loop),
+        ("while_statement", Kind::Loop),
+        ("do_statement", Kind::Loop),
+        ("array", Kind::Array),
+        ("call_expression", Kind::FunctionCall),
+    ],
+};
+
+const PYTHON_KINDS: NodeKinds = NodeKinds {
+    imports: &["import_statement", "import_from_statement"],
+    functions: &["function_definition"],
+    structs: &["class_definition"],
+    enums: &[],
+    traits: &[],
+    type_aliases: &["type_alias_statement"],
+    impls: &[],
+    constants: &[],
+    globals: &[],
+    modules: &[],
+    foreign_modules: &[],
+    calls: &["call"],
+    member_accesses: &["attribute"],
+    comments: &["comment"],
+    literals: &[
+        ("string", "string"),
+        ("integer", "integer"),
+        ("float", "float"),
+        ("true", "boolean"),
+        ("false", "boolean"),
+    ],
+    control_flow: &[
+        ("if_statement", "if"),
+        ("elif_clause", "if"),
+        ("match_statement", "match"),
+        ("for_statement", "for"),
+        ("while_statement", "while"),
+    ],
+    exits: &[
+        ("return_statement", ExitKind::Return),
+        ("break_statement", ExitKind::Break),
+        ("continue_statement", ExitKind::Continue),
+    ],
+    closures: &["lambda"],
+    parameter_fields: &[
+        ("default_parameter", Some("name"), Some("value")),
+        ("typed_default_parameter", Some("name"), Some("value")),
+        ("typed_parameter", None, None),
+        ("list_splat_pattern", None, None),
+        ("dictionary_splat_pattern", None, None),
+    ],
+    parameter_separators: &["keyword_separator", "positional_separator"],
+    tree: &[
+        ("module", Kind::Root),
+        ("comment", Kind::Comment),
+        ("import_statement", Kind::Import),
+        ("import_from_statement", Kind::Import),
+        ("class_definition", Kind::Struct),
+        ("decorator", Kind::Derive),
+        ("function_definition", Kind::Function),
+        ("assignment", Kind::Variable),
+        ("type_alias_statement", Kind::Type),
+        ("if_statement", Kind::If),
+        ("elif_clause", Kind::If),
+        ("else_clause", Kind::Else),
+        ("for_statement", Kind::Loop),
+        ("while_statement", Kind::Loop),
+        ("tuple", Kind::Tuple),
+        ("list", Kind::Array),
+        ("call", Kind::FunctionCall),
+    ],
+};
+
+impl Language {
+    /// File extensions picked up when walking a directory for this language.
+    pub fn extensions(&self) -> &'static [&'static str] {
+        match self {
+            Language::Rust => &["rs"],
+            Language::JavaScript => &["js", "mjs", "cjs"],
+            Language::Python => &["py"],
+        }
+    }
+
+    pub(crate) fn grammar(&self) -> tree_sitter::Language {
+        match self {
+            Language::Rust => tree_sitter_rust::LANGUAGE.into(),
+            Language::JavaScript => tree_sitter_javascript::LANGUAGE.into(),
+            Language::Python => tree_sitter_python::LANGUAGE.into(),
+        }
+    }
+
+    pub(crate) fn kinds(&self) -> &'static NodeKinds {
+        match self {
+            Language::Rust => &RUST_KINDS,
+            Language::JavaScript => &JAVASCRIPT_KINDS,
+            Language::Python => &PYTHON_KINDS,
+        }
+    }
+}
+
+impl FromStr for Language {
+    type Err = String;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "rust" => Ok(Language::Rust),
+            "javascript" | "js" => Ok(Language::JavaScript),
+            "python" | "py" => Ok(Language::Python),
+            _ => Err(format!(
+                "unknown language `{}` (expected rust, javascript or python)",
+                s
+            )),
+        }
+    }
+}
+
+impl fmt::Display for Language {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Language::Rust => "rust",
+            Language::JavaScript => "javascript",
+            Language::Python => "python",
+        };
+        f.write_str(name)
+    }
+}
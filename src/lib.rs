@@ -0,0 +1,44 @@
+mod diff;
+mod error;
+mod extractor;
+mod language;
+mod markdown;
+mod model;
+mod output_schema;
+mod service;
+mod tree;
+
+pub use diff::diff;
+pub use error::ConversionError;
+pub use extractor::Extractor;
+pub use language::Language;
+pub use markdown::{code_blocks, CodeBlock};
+pub use model::{
+    AttributeArg, AttributeInfo, BindingKind, CallInfo, CallKind, CaptureInfo, ControlFlow,
+    Dispatch, DispatchType, EnumInfo, ExitKind, ExitPoint, Exits, FieldInfo, FunctionInfo,
+    GenericKind, GenericParam, MethodChain, ParameterInfo, Position, StructInfo, StructKind,
+    VariableInfo, VariantInfo, WherePredicate,
+};
+pub use output_schema::output_schema;
+pub use service::{ASTConversionService, SortOrder, SymbolRow, VisibilityFilter, CATEGORIES};
+pub use tree::{FlatThing, Kind, Thing, TreeOptions};
+pub use tree_sitter::{InputEdit, Node, Point};
+
+use serde_json::{json, Value};
+#[cfg(all(feature = "wasm", target_arch = "wasm32"))]
+use wasm_bindgen::prelude::wasm_bindgen;
+
+/// Parse Rust source and return the same JSON document the CLI prints.
+pub fn parse_to_json(code: &str) -> Result<Value, ConversionError> {
+    Ok(ASTConversionService::new(code.to_string())?.generate_json())
+}
+
+/// `parse_to_json` as a JSON string, for callers such as the browser that
+/// can only pass strings across; a failure is returned as `{ "error" }`.
+#[cfg_attr(all(feature = "wasm", target_arch = "wasm32"), wasm_bindgen)]
+pub fn parse_rust_to_json(code: &str) -> String {
+    match parse_to_json(code) {
+        Ok(value) => value.to_string(),
+        Err(err) => json!({ "error": err.to_string() }).to_string(),
+    }
+}
@@ -1,358 +1,1106 @@
-use tree_sitter::{ Node, Parser, Tree};
-use tree_sitter_rust;
-
-use serde_json::{json, Value};
+use jsonpath_rust::JsonPath;
+use owo_colors::{OwoColorize, Style};
+use rayon::prelude::*;
+use serde::Serialize;
+use serde_json::ser::PrettyFormatter;
+use serde_json::{json, Serializer, Value};
+use sha2::{Digest, Sha256};
 use std::env;
 use std::fs;
+use std::io::{self, BufRead, BufWriter, IsTerminal, Read, Write};
+use std::path::Path;
+use std::str::FromStr;
+use std::sync::OnceLock;
+use std::time::{Duration, Instant};
+use walkdir::WalkDir;
 
-struct ASTConversionService {
-    code: String,
-    tree: Tree,
-}
-
-impl ASTConversionService {
-    fn new(code: String) -> Self {
-        let mut parser = Parser::new();
-        parser
-        .set_language(&tree_sitter_rust::LANGUAGE.into()) // Using `language()` function
-        .expect("Error loading Rust grammar");
-        let tree = parser.parse(&code, None).expect("Failed to parse code");
-        ASTConversionService { code, tree }
-    }
-    fn gen_json(&self) -> Value {
-        let root_node = self.tree.root_node();
-        let mut j = json!({});
-        for child in root_node.children(&mut root_node.walk()) {}
-        j
-    }
-    fn generate_json(&self) -> Value {
-        let root_node = self.tree.root_node();
-        json!({
-            "imports": self.extract_imports(root_node),
-            "functions": self.extract_functions(root_node),
-            "structs": self.extract_structs(root_node),
-            "enums": self.extract_enums(root_node),
-            "relations": self.extract_relations(root_node),
-            "constants": self.extract_constants(root_node),
-            "modules_and_impls": self.extract_modules_and_impls(root_node),
-            "metadata": self.extract_metadata(root_node),
-            "nested_items": self.extract_nested(root_node),
-            "globals": self.extract_globals(root_node),
-            "schemas": self.extract_schema(root_node),
-        })
-    }
-    fn extract_imports(&self, node: Node) -> Vec<Value> {
-        let mut imports = Vec::new();
-        for child in node.children(&mut node.walk()) {
-            if child.kind() == "use_declaration" {
-                imports.push(json!({
-                    "name": self.node_text(child)
-                }));
-            }
-        }
-        imports
-    }
-    fn extract_functions(&self, node: Node) -> Vec<Value> {
-        let mut functions = Vec::new();
-        for child in node.children(&mut node.walk()) {
-            if child.kind() == "function_item" {
-                let function_name_node = child.child_by_field_name("name").unwrap();
-                let function_name = self.node_text(function_name_node);
-                let parameters = self.extract_parameters(child);
-                let body = self.node_text(child);
-                let called_methods = self.extract_called_methods(child);
-                let local_variables = self.extract_method_variables(child);
-                functions.push(json!({
-                    "name": function_name,
-                    "parameters": parameters,
-                    "body": body,
-                    "called_methods": called_methods,
-                    "local_variables": local_variables
-                }));
-            }
+use treesitter::{
+    code_blocks, diff, output_schema, ASTConversionService, ConversionError, Kind, Language,
+    SortOrder, SymbolRow, Thing, TreeOptions, VisibilityFilter, CATEGORIES,
+};
+
+// Parse a single file into its `{ "path", "ast" }` entry, recording any
+// failure under `"error"` so one bad file doesn't abort a multi-file run.
+fn convert_file(file_path: &str, options: &Options) -> Value {
+    // Checked before reading, so an oversized file is never loaded at all
+    if let Some(limit) = options.max_file_size {
+        let len = fs::metadata(file_path).map_or(0, |metadata| metadata.len());
+        if len > limit {
+            eprintln!(
+                "Warning: skipping {}: {} bytes is over --max-file-size {}",
+                file_path, len, limit
+            );
+            return json!({
+                "path": file_path,
+                "skipped": "too large",
+            });
         }
-        functions
-    }
-    fn extract_parameters(&self, function_node: Node) -> Vec<Value> {
-        let mut parameters = Vec::new();
-        if let Some(parameters_node) = function_node.child_by_field_name("parameters") {
-            for param in parameters_node.named_children(&mut parameters_node.walk()) {
-                let param_name = self.node_text(param);
-                let param_type = param.child_by_field_name("type").map(|n| self.node_text(n));
-                let is_mutable = param.kind() == "mut";
-                let is_reference = param_name.starts_with('&');
-                let default_value = param
-                    .child_by_field_name("default_value")
-                    .map(|n| self.node_text(n));
-                parameters.push(json!({
-                    "name": param_name,
-                    "type": param_type,
-                    "is_mutable": is_mutable,
-                    "is_reference": is_reference,
-                    "default_value": default_value,
-                }));
+    }
+    if options.markdown {
+        return match fs::read_to_string(file_path) {
+            Ok(markdown) => json!({
+                "path": file_path,
+                "blocks": convert_blocks(&markdown, options),
+            }),
+            Err(err) => json!({
+                "path": file_path,
+                "error": err.to_string(),
+            }),
+        };
+    }
+    let result = fs::read_to_string(file_path)
+        .map_err(|err| err.to_string())
+        .and_then(|code| convert_cached(file_path, code, options));
+    match result {
+        Ok(ast) => json!({
+            "path": file_path,
+            "ast": ast,
+        }),
+        Err(err) => json!({
+            "path": file_path,
+            "error": err,
+        }),
+    }
+}
+
+// `--markdown`: each fenced block of the document in `--language`, extracted
+// on its own as `{ "start_line", "ast" }`, or `"error"` for one that fails
+fn convert_blocks(markdown: &str, options: &Options) -> Value {
+    let blocks = code_blocks(markdown, options.language)
+        .into_iter()
+        .map(|block| {
+            let mut entry = json!({ "start_line": block.start_line });
+            match build_service(block.code, options) {
+                Ok(service) => entry["ast"] = extract(&service, options),
+                Err(err) => entry["error"] = json!(err.to_string()),
             }
-        }
-        parameters
-    }
-    fn extract_called_methods(&self, function_node: Node) -> Vec<Value> {
-        let mut called_methods = Vec::new();
-        for descendant in function_node.children(&mut function_node.walk()) {
-            if descendant.kind() == "call_expression" {
-                if let Some(method_name_node) = descendant.child_by_field_name("function") {
-                    let method_name = self.node_text(method_name_node);
-                    called_methods.push(json!({
-                        "name": method_name
-                    }));
-                }
+            entry
+        })
+        .collect();
+    Value::Array(blocks)
+}
+
+// Reuse the output stored for identical contents and options by an earlier
+// run, or extract it and store it for the next one
+fn convert_cached(label: &str, code: String, options: &Options) -> Result<Value, String> {
+    let cache_file = options.cache_dir().zip(build_id()).map(|(dir, build)| {
+        Path::new(dir).join(format!("{}.json", cache_key(build, &code, options)))
+    });
+    if let Some(cache_file) = &cache_file {
+        let cached = fs::read_to_string(cache_file)
+            .ok()
+            .and_then(|text| serde_json::from_str(&text).ok());
+        if let Some(ast) = cached {
+            if options.verbose {
+                eprintln!("{}: loaded from {}", label, cache_file.display());
             }
+            return Ok(ast);
         }
-        called_methods
-    }
-    fn extract_method_variables(&self, function_node: Node) -> Vec<Value> {
-        let mut variables = Vec::new();
-        for descendant in function_node.children(&mut function_node.walk()) {
-            if descendant.kind() == "let_declaration" {
-                let variable_name = self.node_text(descendant.child_by_field_name("name").unwrap());
-                let value_node = descendant.child_by_field_name("value");
-                let value_type = value_node.map(|n| self.node_text(n));
-                variables.push(json!({
-                    "name": variable_name,
-                    "type": value_type
-                }));
-            }
+    }
+    let started = Instant::now();
+    let service = build_service(code, options).map_err(|err| err.to_string())?;
+    let parsed = started.elapsed();
+    let ast = extract(&service, options);
+    if options.verbose {
+        log_file(
+            label,
+            &service,
+            parsed,
+            Some((started.elapsed() - parsed, &ast)),
+        );
+    }
+    if let Some(cache_file) = &cache_file {
+        // A cache that can't be written only costs the next run a re-parse
+        let written = cache_file
+            .parent()
+            .map_or(Ok(()), fs::create_dir_all)
+            .and_then(|()| fs::write(cache_file, ast.to_string()));
+        if let Err(err) = written {
+            eprintln!("Warning: failed to cache {}: {}", cache_file.display(), err);
         }
-        variables
-    }
-    fn extract_structs(&self, node: Node) -> Vec<Value> {
-        let mut structs = Vec::new();
-        for child in node.children(&mut node.walk()) {
-            if child.kind() == "struct_item" {
-                if let Some(struct_name_node) = child.child_by_field_name("name") {
-                    let struct_name = self.node_text(struct_name_node);
-                    let fields = self.extract_fields(child);
-                    structs.push(json!({
-                        "name": struct_name,
-                        "fields": fields
-                    }));
-                }
+    }
+    Ok(ast)
+}
+
+// `--verbose`: one stderr line per file with its parse and extraction times,
+// node count and how many items each category produced
+fn log_file(
+    label: &str,
+    service: &ASTConversionService,
+    parsed: Duration,
+    extracted: Option<(Duration, &Value)>,
+) {
+    let mut line = format!(
+        "{}: parsed in {:.2?}, {} nodes",
+        label,
+        parsed,
+        service.node_count()
+    );
+    if let Some((elapsed, value)) = extracted {
+        line.push_str(&format!(", extracted in {:.2?}", elapsed));
+        let counts: Vec<String> = match value {
+            Value::Object(object) if object.contains_key("kind") => {
+                let children = object.get("children").and_then(Value::as_array);
+                vec![format!("children={}", children.map_or(0, Vec::len))]
             }
+            Value::Object(object) => object
+                .iter()
+                .filter_map(|(key, value)| Some(format!("{}={}", key, value.as_array()?.len())))
+                .collect(),
+            _ => Vec::new(),
+        };
+        if !counts.is_empty() {
+            line.push_str(&format!(" ({})", counts.join(" ")));
         }
-        structs
-    }
-    fn extract_fields(&self, struct_node: Node) -> Vec<Value> {
-        let mut fields = Vec::new();
-        if let Some(body_node) = struct_node.child_by_field_name("body") {
-            for field in body_node.named_children(&mut body_node.walk()) {
-                let field_name = self.node_text(field.child_by_field_name("name").unwrap());
-                let field_type = field.child_by_field_name("type").map(|n| self.node_text(n));
-                let attributes = self.extract_metadata(field);
-                fields.push(json!({
-                    "name": field_name,
-                    "type": field_type,
-                    "attributes": attributes
-                }));
+    }
+    eprintln!("{}", line);
+}
+
+// SHA-256 of the source plus everything that changes the extracted output, so
+// a different build or different flags never read a stale entry
+fn cache_key(build: &str, code: &str, options: &Options) -> String {
+    let mut hasher = Sha256::new();
+    hasher.update(build);
+    hasher.update(format!(
+        "\0{}\0{:?}\0{}\0{}\0{:?}\0{:?}\0{:?}\0{}\0{}\0{}\0{}\0{}\0",
+        options.language,
+        options.mode,
+        options.flat,
+        options.stats_only,
+        options.visibility_filter(),
+        options.sort,
+        options.filter,
+        options.include_unknown,
+        options.strip_comments,
+        options.exclude_bodies,
+        options.module_paths,
+        options.max_depth
+    ));
+    hasher.update(code);
+    format!("{:x}", hasher.finalize())
+}
+
+// SHA-256 of the running executable, which changes with any change to the
+// extractors even when the crate version doesn't. None, and so no caching,
+// when the executable can't be read.
+fn build_id() -> Option<&'static str> {
+    static BUILD_ID: OnceLock<Option<String>> = OnceLock::new();
+    BUILD_ID
+        .get_or_init(|| {
+            let executable = fs::read(env::current_exe().ok()?).ok()?;
+            Some(format!("{:x}", Sha256::digest(executable)))
+        })
+        .as_deref()
+}
+
+fn build_service(code: String, options: &Options) -> Result<ASTConversionService, ConversionError> {
+    let timeout = options.timeout_ms.map(Duration::from_millis);
+    let mut service = ASTConversionService::with_timeout(code, options.language, timeout)?;
+    service.set_strip_comments(options.strip_comments);
+    service.set_visibility_filter(options.visibility_filter());
+    service.set_exclude_bodies(options.exclude_bodies);
+    service.set_module_paths(options.module_paths);
+    service.set_sort_order(options.sort);
+    Ok(service)
+}
+
+// The categories selected by `--filter`, or everything by default; in tree
+// mode, the recursive node tree instead, or with `--flat` its nodes as one
+// list. `--stats-only` counts the same categories instead of emitting them.
+fn extract(service: &ASTConversionService, options: &Options) -> Value {
+    if options.stats_only {
+        return match &options.filter {
+            Some(filter) => {
+                let categories: Vec<&str> = filter.iter().map(String::as_str).collect();
+                service.stats_for(&categories)
             }
+            None => service.stats(),
+        };
+    }
+    if options.flat {
+        return json!(service.generate_tree(&tree_options(options)).flatten());
+    }
+    if options.mode == Mode::Tree {
+        return json!(service.generate_tree(&tree_options(options)));
+    }
+    match &options.filter {
+        Some(filter) => {
+            let categories: Vec<&str> = filter.iter().map(String::as_str).collect();
+            service.generate_json_for(&categories)
         }
-        fields
-    }
-    fn extract_enums(&self, node: Node) -> Vec<Value> {
-        let mut enums = Vec::new();
-        for child in node.children(&mut node.walk()) {
-            if child.kind() == "enum_item" {
-                let enum_name_node = child.child_by_field_name("name").unwrap();
-                let enum_name = self.node_text(enum_name_node);
-                let variants = self.extract_variants(child);
-                enums.push(json!({
-                    "name": enum_name,
-                    "variants": variants
-                }));
-            }
+        None => service.generate_json(),
+    }
+}
+
+fn tree_options(options: &Options) -> TreeOptions {
+    TreeOptions {
+        include_unknown: options.include_unknown,
+        max_depth: options.max_depth,
+        exclude_bodies: options.exclude_bodies,
+    }
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum Format {
+    Json,
+    Yaml,
+    Toml,
+    Csv,
+    Dot,
+    Ndjson,
+}
+
+impl Format {
+    // Formats rendered from the parsed service rather than from the JSON
+    // value, which only make sense for a single input
+    fn is_single_input(&self) -> bool {
+        matches!(self, Format::Csv | Format::Dot | Format::Ndjson)
+    }
+}
+
+impl FromStr for Format {
+    type Err = String;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "json" => Ok(Format::Json),
+            "yaml" => Ok(Format::Yaml),
+            "toml" => Ok(Format::Toml),
+            "csv" => Ok(Format::Csv),
+            "dot" => Ok(Format::Dot),
+            "ndjson" => Ok(Format::Ndjson),
+            _ => Err(format!(
+                "unknown format `{}` (expected json, yaml, toml, csv, dot or ndjson)",
+                s
+            )),
         }
-        enums
-    }
-    fn extract_variants(&self, enum_node: Node) -> Vec<Value> {
-        let mut variants = Vec::new();
-        if let Some(body_node) = enum_node.child_by_field_name("body") {
-            for variant in body_node.named_children(&mut body_node.walk()) {
-                let variant_name = self.node_text(variant.child_by_field_name("name").unwrap());
-                variants.push(json!({
-                    "name": variant_name
-                }));
-            }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+enum Mode {
+    Flat,
+    Tree,
+}
+
+impl FromStr for Mode {
+    type Err = String;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "flat" => Ok(Mode::Flat),
+            "tree" => Ok(Mode::Tree),
+            _ => Err(format!("unknown mode `{}` (expected flat or tree)", s)),
         }
-        variants
-    }
-    fn extract_relations(&self, node: Node) -> Vec<Value> {
-        let mut relations = Vec::new();
-        for child in node.children(&mut node.walk()) {
-            if child.kind() == "impl_item" {
-                if let Some(name_node) = child.child_by_field_name("name") {
-                    let type_name = self.node_text(name_node);
-                    let trait_node = child.child_by_field_name("trait");
-                    let trait_name = trait_node.map(|n| self.node_text(n));
-                    let generic_params = child
-                        .child_by_field_name("generic_parameters")
-                        .map(|n| self.node_text(n));
-                    relations.push(json!({
-                        "type": "impl",
-                        "for": type_name,
-                        "trait": trait_name,
-                        "generics": generic_params,
-                    }));
+    }
+}
+
+// Command-line options; everything that isn't a flag is an input path
+struct Options {
+    paths: Vec<String>,
+    output: Option<String>,
+    format: Format,
+    mode: Mode,
+    flat: bool,
+    tree_view: bool,
+    language: Language,
+    threads: Option<usize>,
+    timeout_ms: Option<u64>,
+    markdown: bool,
+    max_file_size: Option<u64>,
+    compact: bool,
+    indent: usize,
+    filter: Option<Vec<String>>,
+    select: Option<String>,
+    print_schema: bool,
+    include_unknown: bool,
+    cache_dir: Option<String>,
+    no_cache: bool,
+    strip_comments: bool,
+    max_depth: usize,
+    recursive: bool,
+    exclude: Vec<glob::Pattern>,
+    verbose: bool,
+    stats_only: bool,
+    sexp: bool,
+    repl: bool,
+    diff: bool,
+    public_only: bool,
+    include_crate_visible: bool,
+    exclude_bodies: bool,
+    module_paths: bool,
+    manifest: Option<String>,
+    sort: SortOrder,
+}
+
+impl Options {
+    fn parse(args: &[String]) -> Result<Self, String> {
+        let mut options = Options {
+            paths: Vec::new(),
+            output: None,
+            format: Format::Json,
+            mode: Mode::Flat,
+            flat: false,
+            tree_view: false,
+            language: Language::Rust,
+            threads: None,
+            timeout_ms: None,
+            markdown: false,
+            max_file_size: None,
+            compact: false,
+            indent: DEFAULT_INDENT,
+            filter: None,
+            select: None,
+            print_schema: false,
+            include_unknown: false,
+            cache_dir: None,
+            no_cache: false,
+            strip_comments: false,
+            max_depth: TreeOptions::DEFAULT_MAX_DEPTH,
+            recursive: false,
+            exclude: Vec::new(),
+            verbose: false,
+            stats_only: false,
+            sexp: false,
+            repl: false,
+            diff: false,
+            public_only: false,
+            include_crate_visible: false,
+            exclude_bodies: false,
+            module_paths: false,
+            manifest: None,
+            sort: SortOrder::Source,
+        };
+        let mut iter = args.iter();
+        while let Some(arg) = iter.next() {
+            match arg.as_str() {
+                "--output" | "-o" => {
+                    let path = iter.next().ok_or(format!("{} requires a path", arg))?;
+                    options.output = Some(path.clone());
+                }
+                "--format" => {
+                    let format = iter.next().ok_or(format!("{} requires a value", arg))?;
+                    options.format = format.parse()?;
+                }
+                "--mode" => {
+                    let mode = iter.next().ok_or(format!("{} requires a value", arg))?;
+                    options.mode = mode.parse()?;
+                }
+                "--language" => {
+                    let language = iter.next().ok_or(format!("{} requires a value", arg))?;
+                    options.language = language.parse()?;
+                }
+                "--threads" => {
+                    let threads = iter.next().ok_or(format!("{} requires a value", arg))?;
+                    let threads = threads
+                        .parse()
+                        .map_err(|_| format!("invalid thread count `{}`", threads))?;
+                    options.threads = Some(threads);
+                }
+                "--timeout-ms" => {
+                    let millis = iter.next().ok_or(format!("{} requires a value", arg))?;
+                    let millis = millis
+                        .parse()
+                        .map_err(|_| format!("invalid timeout `{}`", millis))?;
+                    options.timeout_ms = Some(millis);
+                }
+                "--max-file-size" => {
+                    let bytes = iter.next().ok_or(format!("{} requires a value", arg))?;
+                    let bytes = bytes
+                        .parse()
+                        .map_err(|_| format!("invalid file size `{}`", bytes))?;
+                    options.max_file_size = Some(bytes);
+                }
+                "--indent" => {
+                    let width = iter.next().ok_or(format!("{} requires a value", arg))?;
+                    options.indent = width
+                        .parse()
+                        .map_err(|_| format!("invalid indent `{}`", width))?;
+                }
+                "--sort" => {
+                    let order = iter.next().ok_or(format!("{} requires a value", arg))?;
+                    options.sort = order.parse()?;
+                }
+                "--max-depth" => {
+                    let depth = iter.next().ok_or(format!("{} requires a value", arg))?;
+                    options.max_depth = depth
+                        .parse()
+                        .map_err(|_| format!("invalid depth `{}`", depth))?;
+                }
+                "--filter" => {
+                    let list = iter.next().ok_or(format!("{} requires a value", arg))?;
+                    let mut filter = Vec::new();
+                    for category in list.split(',').map(str::trim) {
+                        if !CATEGORIES.contains(&category) {
+                            return Err(format!(
+                                "unknown category `{}` (expected one of {})",
+                                category,
+                                CATEGORIES.join(", ")
+                            ));
+                        }
+                        filter.push(category.to_string());
+                    }
+                    options.filter = Some(filter);
                 }
-            } else if child.kind() == "attribute_item" {
-                if let Some(attribute_text) = self.extract_metadata(child).get(0) {
-                    if attribute_text["attribute"]
-                        .as_str()
-                        .unwrap_or("")
-                        .contains("derive")
-                    {
-                        relations.push(json!({
-                            "type": "derive",
-                            "details": attribute_text
-                        }));
+                "--select" => {
+                    let path = iter.next().ok_or(format!("{} requires a JSONPath", arg))?;
+                    // Checked up front, so a typo fails before any parsing
+                    if let Err(err) = Value::Null.query(path) {
+                        return Err(format!("invalid JSONPath `{}`: {}", path, err));
                     }
+                    options.select = Some(path.clone());
                 }
+                "--manifest" => {
+                    let path = iter.next().ok_or(format!("{} requires a path", arg))?;
+                    options.manifest = Some(path.clone());
+                }
+                "--cache-dir" => {
+                    let path = iter.next().ok_or(format!("{} requires a path", arg))?;
+                    options.cache_dir = Some(path.clone());
+                }
+                "--no-cache" => options.no_cache = true,
+                "--verbose" | "-v" => options.verbose = true,
+                "--recursive" | "-r" => options.recursive = true,
+                "--exclude" => {
+                    let pattern = iter.next().ok_or(format!("{} requires a pattern", arg))?;
+                    let pattern = glob::Pattern::new(pattern)
+                        .map_err(|err| format!("invalid pattern `{}`: {}", pattern, err))?;
+                    options.exclude.push(pattern);
+                }
+                "--strip-comments" => options.strip_comments = true,
+                "--exclude-bodies" => options.exclude_bodies = true,
+                "--module-paths" => options.module_paths = true,
+                "--flat" => options.flat = true,
+                "--tree-view" => options.tree_view = true,
+                "--markdown" => options.markdown = true,
+                "--compact" => options.compact = true,
+                "--pretty" => options.compact = false,
+                "--print-schema" => options.print_schema = true,
+                "--stats-only" => options.stats_only = true,
+                "--sexp" => options.sexp = true,
+                "--repl" => options.repl = true,
+                "--diff" => options.diff = true,
+                "--public-only" => options.public_only = true,
+                "--include-crate-visible" => options.include_crate_visible = true,
+                "--include-unknown" => options.include_unknown = true,
+                _ => options.paths.push(arg.clone()),
             }
         }
-        relations
-    }
-    fn extract_constants(&self, node: Node) -> Vec<Value> {
-        let mut constants = Vec::new();
-        for child in node.children(&mut node.walk()) {
-            if child.kind() == "const_item" {
-                let constant_name = self.node_text(child.child_by_field_name("name").unwrap());
-                let constant_value =
-                    self.node_text(child.child_by_field_name("value").unwrap_or(child));
-                constants.push(json!({
-                    "name": constant_name,
-                    "value": constant_value
-                }));
-            }
+        Ok(options)
+    }
+
+    fn visibility_filter(&self) -> VisibilityFilter {
+        match (self.public_only, self.include_crate_visible) {
+            (false, _) => VisibilityFilter::All,
+            (true, false) => VisibilityFilter::Public,
+            (true, true) => VisibilityFilter::PublicAndCrate,
         }
-        constants
-    }
-    fn extract_modules_and_impls(&self, node: Node) -> Vec<Value> {
-        let mut modules = Vec::new();
-        for child in node.children(&mut node.walk()) {
-            if child.kind() == "mod_item" || child.kind() == "impl_item" {
-                if let Some(name_node) = child.child_by_field_name("name") {
-                    let name = self.node_text(name_node);
-                    modules.push(json!({
-                        "type": child.kind(),
-                        "name": name,
-                        "children": self.extract_nested(child),
-                    }));
+    }
+
+    // Whether the output is the tree mode's nodes rather than the extracted
+    // categories, which the category options have no say in
+    fn builds_tree(&self) -> bool {
+        self.mode == Mode::Tree || self.flat || self.tree_view || self.format == Format::Ndjson
+    }
+
+    // Where multi-file runs cache their per-file output, if anywhere
+    fn cache_dir(&self) -> Option<&str> {
+        if self.no_cache {
+            return None;
+        }
+        self.cache_dir.as_deref()
+    }
+}
+
+// Expand glob patterns and (with `--recursive`) directories into the files
+// to parse, minus `--exclude` matches. The flag is set when anything was
+// expanded, since then the output is a list even for a single match.
+fn expand_paths(options: &Options) -> Result<(Vec<String>, bool), String> {
+    let excluded = |path: &Path| options.exclude.iter().any(|p| p.matches_path(path));
+    let mut paths = Vec::new();
+    let mut expanded = false;
+    for arg in &options.paths {
+        let matches: Vec<String> = if arg.contains(['*', '?', '[']) {
+            expanded = true;
+            glob::glob(arg)
+                .map_err(|err| format!("invalid pattern `{}`: {}", arg, err))?
+                .filter_map(Result::ok)
+                .map(|path| path.to_string_lossy().into_owned())
+                .collect()
+        } else {
+            vec![arg.clone()]
+        };
+        for path in matches {
+            if !Path::new(&path).is_dir() {
+                if !excluded(Path::new(&path)) {
+                    paths.push(path);
                 }
+                continue;
             }
-        }
-        modules
-    }
-    fn extract_metadata(&self, node: Node) -> Vec<Value> {
-        let mut metadata = Vec::new();
-        for child in node.children(&mut node.walk()) {
-            if child.kind() == "attribute_item" {
-                let attribute_text = self.node_text(child);
-                metadata.push(json!({
-                    "attribute": attribute_text
-                }));
+            if !options.recursive {
+                return Err(format!(
+                    "{} is a directory (pass --recursive to walk it)",
+                    path
+                ));
             }
-        }
-        metadata
-    }
-    fn extract_nested(&self, node: Node) -> Vec<Value> {
-        let mut nested_items = Vec::new();
-        for child in node.children(&mut node.walk()) {
-            println!("===> child.kind() = {}", child.kind());
-            // match child.kind() {
-            // "mod_item" | "impl_item" | "function_item" | "struct_item" | "fn" => {
-            if let Some(name_node) = child.child_by_field_name("name") {
-                nested_items.push(json!({
-                    "type": child.kind(),
-                    "name": self.node_text(name_node),
-                    "children": self.extract_nested(child),
-                }));
+            expanded = true;
+            let extensions = if options.markdown {
+                MARKDOWN_EXTENSIONS
             } else {
-                let new_nested_items = self.extract_nested(child);
-                // nested_items.push(json!({
-                //     "type": child.kind(),
-                //     "children": self.extract_nested(child),
-                // }));
+                options.language.extensions()
+            };
+            let walker = WalkDir::new(&path)
+                .sort_by_file_name()
+                .into_iter()
+                .filter_entry(|entry| !excluded(entry.path()));
+            for entry in walker {
+                let entry = entry.map_err(|err| err.to_string())?;
+                let is_source = entry
+                    .path()
+                    .extension()
+                    .is_some_and(|extension| extensions.iter().any(|wanted| extension == *wanted));
+                if entry.file_type().is_file() && is_source {
+                    paths.push(entry.path().to_string_lossy().into_owned());
+                }
             }
-            // }
-            // _ => {}
-            // }
         }
-        nested_items
-    }
-    fn extract_globals(&self, node: Node) -> Vec<Value> {
-        let mut globals = Vec::new();
-        for child in node.children(&mut node.walk()) {
-            if child.kind() == "static_item" {
-                let global_name = self.node_text(child.child_by_field_name("name").unwrap());
-                let global_value =
-                    self.node_text(child.child_by_field_name("value").unwrap_or(child));
-                globals.push(json!({
-                    "name": global_name,
-                    "value": global_value,
-                }));
-            }
+    }
+    Ok((paths, expanded))
+}
+
+// `--manifest`: one `{ "path": ..., "id": ... }` object per line, blank lines
+// skipped. The optional `id` is copied onto that file's output as is.
+fn read_manifest(manifest: &str) -> Result<Vec<(String, Option<Value>)>, String> {
+    let text = fs::read_to_string(manifest)
+        .map_err(|err| format!("failed to read {}: {}", manifest, err))?;
+    let mut entries = Vec::new();
+    for (index, line) in text.lines().enumerate() {
+        if line.trim().is_empty() {
+            continue;
+        }
+        let invalid = |reason: String| format!("{}:{}: {}", manifest, index + 1, reason);
+        let mut entry: Value =
+            serde_json::from_str(line).map_err(|err| invalid(err.to_string()))?;
+        let path = entry["path"]
+            .as_str()
+            .ok_or_else(|| invalid("expected an object with a string \"path\"".to_string()))?
+            .to_string();
+        let id = entry.as_object_mut().and_then(|entry| entry.remove("id"));
+        entries.push((path, id));
+    }
+    Ok(entries)
+}
+
+const DEFAULT_INDENT: usize = 2;
+// The outputs `Options::builds_tree` covers, for rejecting flags they ignore
+const TREE_OUTPUTS: &str = "--mode tree, --flat, --tree-view or ndjson output";
+const TREE_VIEW_PREVIEW: usize = 60;
+const MARKDOWN_EXTENSIONS: &[&str] = &["md", "markdown"];
+
+fn print_usage(program: &str) {
+    eprintln!("Usage: {} [options] <rust_source_file>...", program);
+    eprintln!("       cat <rust_source_file> | {} [options]", program);
+    eprintln!();
+    eprintln!("Options:");
+    eprintln!("  -o, --output <path>     write the result to <path> instead of stdout");
+    eprintln!("      --format <format>   json (default), yaml, toml, csv (symbol table),");
+    eprintln!("                          dot (type/impl relation graph) or ndjson");
+    eprintln!("                          (one top-level tree item per line)");
+    eprintln!("      --mode <mode>       flat (default) categorized lists, or tree");
+    eprintln!("                          for the recursive node tree");
+    eprintln!("      --flat              the tree mode nodes as one list, each with an `id`");
+    eprintln!("                          and its container's `parent_id`");
+    eprintln!("      --tree-view         print the tree mode nodes as an indented tree, one");
+    eprintln!("                          line each, colored when writing to a terminal");
+    eprintln!("      --include-unknown   in tree mode, keep unmapped nodes with their raw_kind");
+    eprintln!("      --max-depth <n>     in tree mode, stop descending after <n> levels");
+    eprintln!(
+        "                          (default {})",
+        TreeOptions::DEFAULT_MAX_DEPTH
+    );
+    eprintln!("      --language <lang>   rust (default), javascript or python");
+    eprintln!("      --markdown          read Markdown and extract each fenced block of");
+    eprintln!("                          --language, with the line it starts on");
+    eprintln!("      --manifest <path>   also parse the files listed in a JSON Lines file of");
+    eprintln!(
+        "                          {{\"path\", \"id\"}} objects, tagging each output with its id"
+    );
+    eprintln!("  -r, --recursive         walk directories for source files of --language");
+    eprintln!("      --exclude <glob>    skip matching paths (repeatable)");
+    eprintln!("      --threads <n>       parse at most <n> files in parallel");
+    eprintln!("      --timeout-ms <n>    give up on a file that takes longer than <n> ms");
+    eprintln!("                          to parse (default unlimited)");
+    eprintln!("      --max-file-size <n> with several inputs, skip files over <n> bytes");
+    eprintln!("      --compact           single-line JSON instead of pretty-printed");
+    eprintln!(
+        "      --indent <n>        indent pretty JSON by <n> spaces (default {}; 0 is",
+        DEFAULT_INDENT
+    );
+    eprintln!("                          the same as --compact)");
+    eprintln!("      --strip-comments    leave comments out of function bodies");
+    eprintln!("      --exclude-bodies    leave out function bodies and macro arguments");
+    eprintln!("      --filter <keys>     only emit these comma-separated categories");
+    eprintln!("      --select <path>     print only the values a JSONPath matches in the");
+    eprintln!("                          output, `$.functions[*].name`");
+    eprintln!("      --module-paths      give functions, structs and enums their path through");
+    eprintln!("                          the file's modules, `outer::inner::Item`");
+    eprintln!("      --sort <order>      order each category's items by source (default),");
+    eprintln!("                          name, or kind then name");
+    eprintln!("      --public-only       leave out functions, structs, enums, fields and");
+    eprintln!("                          methods that aren't `pub`");
+    eprintln!("      --include-crate-visible");
+    eprintln!("                          with --public-only, keep `pub(crate)` items too");
+    eprintln!("      --stats-only        emit item counts per category, line and error");
+    eprintln!("                          counts instead of the extracted items");
+    eprintln!("      --cache-dir <path>  cache each file's output for multi-file runs in");
+    eprintln!("                          <path>, reused until the file, flags or build change");
+    eprintln!("      --no-cache          ignore --cache-dir: always re-parse, without reading");
+    eprintln!("                          or writing the cache");
+    eprintln!("  -v, --verbose           log per-file timings and counts to stderr");
+    eprintln!("      --diff              compare two files' top-level items: added, removed");
+    eprintln!("                          and changed (by signature for functions)");
+    eprintln!("      --sexp              print tree-sitter's s-expression of the syntax tree");
+    eprintln!("                          instead of extracting anything");
+    eprintln!("      --repl              parse the file once, then run each tree-sitter query");
+    eprintln!("                          read from stdin and print its captures");
+    eprintln!("      --print-schema      print the JSON Schema of the output and exit");
+}
+
+// `--select` applies to whatever document is about to be printed, so it
+// works the same on one file, a multi-file list or a `--diff` report
+fn render(value: &Value, options: &Options) -> String {
+    let selected;
+    let value = match &options.select {
+        Some(path) => {
+            selected = select(value, path);
+            &selected
+        }
+        None => value,
+    };
+    match options.format {
+        Format::Json => to_json(value, options),
+        Format::Yaml => serde_yaml::to_string(value).unwrap().trim_end().to_string(),
+        Format::Toml => toml::to_string(&toml_compatible(value))
+            .unwrap()
+            .trim_end()
+            .to_string(),
+        Format::Csv | Format::Dot | Format::Ndjson => {
+            unreachable!("rendered from the parsed service")
         }
-        globals
-    }
-    fn extract_schema(&self, node: Node) -> Vec<Value> {
-        let mut schemas = Vec::new();
-        for child in node.children(&mut node.walk()) {
-            if child.kind() == "struct_item" {
-                let struct_name = self.node_text(child.child_by_field_name("name").unwrap());
-                let attributes = self.extract_metadata(child);
-                let fields = self.extract_fields(child);
-                // Extract relationships based on field attributes or annotations
-                let relationships = fields
+    }
+}
+
+// Pretty-printed with `--indent` spaces, or on one line for `--compact` or
+// an indent of 0
+fn to_json(value: &Value, options: &Options) -> String {
+    if options.compact || options.indent == 0 {
+        return serde_json::to_string(value).unwrap();
+    }
+    let indent = " ".repeat(options.indent);
+    let formatter = PrettyFormatter::with_indent(indent.as_bytes());
+    let mut serializer = Serializer::with_formatter(Vec::new(), formatter);
+    value.serialize(&mut serializer).unwrap();
+    String::from_utf8(serializer.into_inner()).unwrap()
+}
+
+// The values `path` matches in `value`, in document order
+fn select(value: &Value, path: &str) -> Value {
+    let matches = value
+        .query(path)
+        .expect("--select paths are validated while parsing the arguments");
+    Value::Array(matches.into_iter().cloned().collect())
+}
+
+// TOML has no null and needs a table at the top: nulls are dropped (absent
+// keys, or skipped array entries) and a top-level array, as produced for
+// several input files, is wrapped as `files = [...]`
+fn toml_compatible(value: &Value) -> Value {
+    fn drop_nulls(value: &Value) -> Value {
+        match value {
+            Value::Object(object) => Value::Object(
+                object
                     .iter()
-                    .filter_map(|field| {
-                        if let Some(attribute) = field.get("attributes") {
-                            if attribute.as_str().unwrap_or("").contains("foreign_key") {
-                                Some(json!({
-                                    "field": field.get("name"),
-                                    "relationship": "foreign_key"
-                                }))
-                            } else {
-                                None
-                            }
-                        } else {
-                            None
-                        }
-                    })
-                    .collect::<Vec<_>>();
-                schemas.push(json!({
-                    "struct": struct_name,
-                    "attributes": attributes,
-                    "fields": fields,
-                    "relationships": relationships
-                }));
+                    .filter(|(_, value)| !value.is_null())
+                    .map(|(key, value)| (key.clone(), drop_nulls(value)))
+                    .collect(),
+            ),
+            Value::Array(items) => Value::Array(
+                items
+                    .iter()
+                    .filter(|item| !item.is_null())
+                    .map(drop_nulls)
+                    .collect(),
+            ),
+            _ => value.clone(),
+        }
+    }
+    match drop_nulls(value) {
+        Value::Object(object) => Value::Object(object),
+        other => json!({ "files": other }),
+    }
+}
+
+fn render_csv(rows: &[SymbolRow]) -> String {
+    let mut writer = csv::Writer::from_writer(Vec::new());
+    for row in rows {
+        writer.serialize(row).unwrap();
+    }
+    let bytes = writer.into_inner().unwrap();
+    String::from_utf8(bytes).unwrap().trim_end().to_string()
+}
+
+// `--diff`: compare the APIs extracted from exactly two files
+fn emit_diff(paths: &[String], options: &Options) {
+    let [before, after] = paths else {
+        eprintln!("Error: --diff takes exactly two input files");
+        std::process::exit(1);
+    };
+    if options.format.is_single_input() {
+        eprintln!("Error: --diff takes json, yaml or toml output");
+        std::process::exit(1);
+    }
+    let extract_api = |path: &String| {
+        let service = fs::read_to_string(path)
+            .map_err(|err| err.to_string())
+            .and_then(|code| build_service(code, options).map_err(|err| err.to_string()));
+        match service {
+            Ok(service) => service.generate_json(),
+            Err(err) => {
+                eprintln!("Error: {}: {}", path, err);
+                std::process::exit(1);
+            }
+        }
+    };
+    let report = diff(&extract_api(before), &extract_api(after));
+    emit(&render(&report, options), options.output.as_deref());
+}
+
+// `--repl`: one query per line of stdin until EOF, each printed as its list
+// of captures. The prompt goes to stderr so that stdout stays parseable.
+// A query that doesn't compile is reported and the loop goes on.
+fn run_repl(service: &ASTConversionService, options: &Options) {
+    let mut lines = io::stdin().lock().lines();
+    loop {
+        eprint!("query> ");
+        let _ = io::stderr().flush();
+        let Some(Ok(line)) = lines.next() else {
+            eprintln!();
+            return;
+        };
+        let source = line.trim();
+        if source.is_empty() {
+            continue;
+        }
+        match service.query(source) {
+            Ok(captures) => emit(
+                &render(&json!(captures), options),
+                options.output.as_deref(),
+            ),
+            Err(err) => eprintln!("Error: {}", err),
+        }
+    }
+}
+
+// `--tree-view`: one line per node, drawn like tree(1), with its kind
+// (colored by kind when `color` is set), name and the start of its text
+fn render_tree_view(root: &Thing, color: bool) -> String {
+    let mut lines = Vec::new();
+    tree_view_lines(root, String::new(), "", color, &mut lines);
+    lines.join("\n")
+}
+
+fn tree_view_lines(
+    thing: &Thing,
+    mut line: String,
+    indent: &str,
+    color: bool,
+    lines: &mut Vec<String>,
+) {
+    let kind = match &thing.raw_kind {
+        Some(raw_kind) => raw_kind.clone(),
+        None => format!("{:?}", thing.kind),
+    };
+    let paint = |text: &str, style: Style| {
+        if color {
+            text.style(style).to_string()
+        } else {
+            text.to_string()
+        }
+    };
+    line.push_str(&paint(&kind, kind_style(thing.kind)));
+    if let Some(name) = &thing.name {
+        line.push(' ');
+        line.push_str(&paint(name, Style::new().bold()));
+    }
+    let preview = text_preview(&thing.text);
+    // The root holds the whole file, and the outermost one just its kind
+    if thing.kind != Kind::Root && !preview.is_empty() {
+        line.push_str("  ");
+        line.push_str(&paint(&preview, Style::new().dimmed()));
+    }
+    if thing.truncated {
+        line.push_str(" [truncated]");
+    }
+    lines.push(line);
+    for (index, child) in thing.children.iter().enumerate() {
+        let (branch, next) = if index + 1 == thing.children.len() {
+            ("└── ", "    ")
+        } else {
+            ("├── ", "│   ")
+        };
+        let child_indent = format!("{}{}", indent, next);
+        let child_line = format!("{}{}", indent, branch);
+        tree_view_lines(child, child_line, &child_indent, color, lines);
+    }
+}
+
+fn kind_style(kind: Kind) -> Style {
+    match kind {
+        Kind::Function | Kind::Method => Style::new().green(),
+        Kind::Struct | Kind::Enum | Kind::Type | Kind::Field | Kind::Variant => Style::new().cyan(),
+        Kind::Module | Kind::Trait | Kind::Impl | Kind::Derive => Style::new().magenta(),
+        Kind::If | Kind::Else | Kind::Loop => Style::new().yellow(),
+        Kind::Import => Style::new().blue(),
+        Kind::Comment | Kind::DocComment => Style::new().bright_black(),
+        _ => Style::new(),
+    }
+}
+
+// The first line of `text`, whitespace collapsed and cut at
+// `TREE_VIEW_PREVIEW` characters, with `…` when anything was cut
+fn text_preview(text: &str) -> String {
+    let first = text.trim().lines().next().unwrap_or_default();
+    let collapsed = first.split_whitespace().collect::<Vec<_>>().join(" ");
+    let mut preview: String = collapsed.chars().take(TREE_VIEW_PREVIEW).collect();
+    if preview.len() < collapsed.len() || text.trim().contains('\n') {
+        preview.push('…');
+    }
+    preview
+}
+
+// Print to stdout, or write to `--output` when given
+fn emit(text: &str, output: Option<&str>) {
+    match output {
+        Some(path) => {
+            if let Err(err) = fs::write(path, format!("{}\n", text)) {
+                eprintln!("Error: failed to write {}: {}", path, err);
+                std::process::exit(1);
             }
         }
-        schemas
+        None => println!("{}", text),
+    }
+}
+
+// Write each item as one line of JSON as it is produced, so the full list
+// is never held in memory
+fn emit_ndjson(mut items: impl Iterator<Item = Thing>, output: Option<&str>) {
+    let mut writer: Box<dyn Write> = match output {
+        Some(path) => match fs::File::create(path) {
+            Ok(file) => Box::new(BufWriter::new(file)),
+            Err(err) => {
+                eprintln!("Error: failed to write {}: {}", path, err);
+                std::process::exit(1);
+            }
+        },
+        None => Box::new(BufWriter::new(io::stdout().lock())),
+    };
+    let mut result = items.try_for_each(|item| {
+        serde_json::to_writer(&mut writer, &item)?;
+        writeln!(writer).map_err(serde_json::Error::io)
+    });
+    if result.is_ok() {
+        result = writer.flush().map_err(serde_json::Error::io);
     }
-    fn node_text(&self, node: Node) -> String {
-        self.code[node.byte_range()].to_string()
+    if let Err(err) = result {
+        eprintln!(
+            "Error: failed to write {}: {}",
+            output.unwrap_or("stdout"),
+            err
+        );
+        std::process::exit(1);
     }
 }
 
 fn main() {
     let args: Vec<String> = env::args().collect();
-    if args.len() < 2 {
-        eprintln!("Usage: {} <rust_source_file>", args[0]);
+    let options = match Options::parse(&args[1..]) {
+        Ok(options) => options,
+        Err(err) => {
+            eprintln!("Error: {}", err);
+            print_usage(&args[0]);
+            std::process::exit(1);
+        }
+    };
+    let output = options.output.as_deref();
+    if options.print_schema {
+        // Always JSON, whatever `--format` says: the schema describes the
+        // JSON document
+        return emit(&to_json(&output_schema(), &options), output);
+    }
+    let (paths, expanded) = match expand_paths(&options) {
+        Ok(expanded) => expanded,
+        Err(err) => {
+            eprintln!("Error: {}", err);
+            std::process::exit(1);
+        }
+    };
+    if options.stats_only && options.format.is_single_input() {
+        eprintln!("Error: --stats-only takes json, yaml or toml output");
+        std::process::exit(1);
+    }
+    if options.flat && options.format.is_single_input() {
+        eprintln!("Error: --flat takes json, yaml or toml output");
+        std::process::exit(1);
+    }
+    if options.markdown && options.format.is_single_input() {
+        eprintln!("Error: --markdown takes json, yaml or toml output");
+        std::process::exit(1);
+    }
+    if options.markdown && (options.sexp || options.repl || options.diff) {
+        eprintln!("Error: --markdown can't be combined with --sexp, --repl or --diff");
+        std::process::exit(1);
+    }
+    if options.select.is_some() && options.format.is_single_input() {
+        eprintln!("Error: --select takes json, yaml or toml output");
+        std::process::exit(1);
+    }
+    // Options of the extracted categories, which the tree mode's nodes
+    // would silently ignore
+    let category_options = [
+        ("--public-only", options.public_only),
+        ("--filter", options.filter.is_some()),
+        ("--sort", options.sort != SortOrder::Source),
+        ("--strip-comments", options.strip_comments),
+        ("--module-paths", options.module_paths),
+    ];
+    if let Some((flag, _)) = category_options
+        .iter()
+        .find(|(_, set)| *set && options.builds_tree())
+    {
+        eprintln!("Error: {} can't be combined with {}", flag, TREE_OUTPUTS);
+        std::process::exit(1);
+    }
+    if options.select.is_some() && (options.sexp || options.tree_view) {
+        eprintln!("Error: --select can't be combined with --sexp or --tree-view");
+        std::process::exit(1);
+    }
+    if options.repl && options.format.is_single_input() {
+        eprintln!("Error: --repl takes json, yaml or toml output");
         std::process::exit(1);
     }
-    let file_path = &args[1];
-    let code = fs::read_to_string(file_path).expect("Failed to read the Rust source file.");
+    if options.diff {
+        return emit_diff(&paths, &options);
+    }
+    let manifest = match options.manifest.as_deref().map(read_manifest) {
+        Some(Ok(entries)) => entries,
+        Some(Err(err)) => {
+            eprintln!("Error: {}", err);
+            std::process::exit(1);
+        }
+        None => Vec::new(),
+    };
+    let batch = paths.len() > 1 || expanded || options.manifest.is_some();
+    if batch && options.format.is_single_input() {
+        eprintln!("Error: csv, dot and ndjson output take a single input file");
+        std::process::exit(1);
+    }
+    if batch && options.sexp {
+        eprintln!("Error: --sexp takes a single input file");
+        std::process::exit(1);
+    }
+    if batch && options.tree_view {
+        eprintln!("Error: --tree-view takes a single input file");
+        std::process::exit(1);
+    }
+    // Queries come from stdin, so the source can't
+    if options.repl && (batch || paths.is_empty()) {
+        eprintln!("Error: --repl takes a single input file");
+        std::process::exit(1);
+    }
+    if batch {
+        // Each task builds its own service (and parser), since tree-sitter's
+        // Parser can't be shared between threads
+        let mut pool = rayon::ThreadPoolBuilder::new();
+        if let Some(threads) = options.threads {
+            pool = pool.num_threads(threads);
+        }
+        let pool = pool.build().expect("Failed to start the thread pool.");
+        let entries: Vec<(String, Option<Value>)> = paths
+            .into_iter()
+            .map(|path| (path, None))
+            .chain(manifest)
+            .collect();
+        let results: Vec<Value> = pool.install(|| {
+            entries
+                .par_iter()
+                .map(|(path, id)| {
+                    let mut result = convert_file(path, &options);
+                    if let Some(id) = id {
+                        result["id"] = id.clone();
+                    }
+                    result
+                })
+                .collect()
+        });
+        emit(&render(&json!(results), &options), output);
+        return;
+    }
+    let label = paths.first().map_or("<stdin>", String::as_str);
+    let code = match paths.first() {
+        Some(file_path) => {
+            fs::read_to_string(file_path).expect("Failed to read the Rust source file.")
+        }
+        None => {
+            // Without a path, read piped source from stdin; keep the usage
+            // message for interactive invocations.
+            let mut stdin = io::stdin();
+            if stdin.is_terminal() {
+                print_usage(&args[0]);
+                std::process::exit(1);
+            }
+            let mut code = String::new();
+            stdin
+                .read_to_string(&mut code)
+                .expect("Failed to read the Rust source from stdin.");
+            code
+        }
+    };
+    if options.markdown {
+        return emit(&render(&convert_blocks(&code, &options), &options), output);
+    }
 
-    let service = ASTConversionService::new(code);
-    let json_output = service.generate_json();
+    let started = Instant::now();
+    let service = match build_service(code, &options) {
+        Ok(service) => service,
+        Err(err) => {
+            eprintln!("Error: {}", err);
+            std::process::exit(1);
+        }
+    };
+    let parsed = started.elapsed();
+    if options.format.is_single_input() && options.verbose {
+        log_file(label, &service, parsed, None);
+    }
+    if options.sexp {
+        return emit(&service.to_sexp(), output);
+    }
+    if options.repl {
+        return run_repl(&service, &options);
+    }
+    if options.tree_view {
+        let color = output.is_none() && io::stdout().is_terminal();
+        let root = service.generate_tree(&tree_options(&options));
+        return emit(&render_tree_view(&root, color), output);
+    }
+    match options.format {
+        Format::Csv => return emit(&render_csv(&service.to_symbol_table()), output),
+        Format::Dot => return emit(&service.to_dot(), output),
+        Format::Ndjson => return emit_ndjson(service.items_with(tree_options(&options)), output),
+        _ => {}
+    }
+    let json_output = extract(&service, &options);
+    if options.verbose {
+        let extracted = started.elapsed() - parsed;
+        log_file(label, &service, parsed, Some((extracted, &json_output)));
+    }
 
-    // Pretty-print the JSON output
-    println!("{}", serde_json::to_string_pretty(&json_output).unwrap());
+    emit(&render(&json_output, &options), output);
 }
@@ -0,0 +1,80 @@
+use serde::{Deserialize, Serialize};
+
+use crate::language::Language;
+
+/// One fenced code block of a Markdown document.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CodeBlock {
+    /// 0-based line of the block's first line of code, so adding it to a
+    /// position inside the block gives the line in the document
+    pub start_line: usize,
+    /// The fence's info string, `rust,no_run`
+    pub info: String,
+    pub code: String,
+}
+
+/// The ```` ``` ```` and `~~~` fenced blocks of `markdown` tagged with
+/// `language`, by name or extension (`rust`, `rs`; rustdoc's `rust,ignore`
+/// counts too). Untagged and other fences are skipped; a fence left open
+/// runs to the end of the document.
+pub fn code_blocks(markdown: &str, language: Language) -> Vec<CodeBlock> {
+    let mut blocks = Vec::new();
+    let mut lines = markdown.lines().enumerate();
+    while let Some((index, line)) = lines.next() {
+        let Some((indent, fence, info)) = opening_fence(line) else {
+            continue;
+        };
+        let mut code = String::new();
+        for (_, line) in lines.by_ref() {
+            if is_closing_fence(line, fence) {
+                break;
+            }
+            // Content is indented relative to the fence, as in CommonMark
+            let strip = line.len() - line.trim_start_matches(' ').len();
+            code.push_str(&line[strip.min(indent)..]);
+            code.push('\n');
+        }
+        let tag = info
+            .split(|c: char| c == ',' || c.is_whitespace())
+            .next()
+            .unwrap_or_default();
+        let tagged =
+            tag.parse::<Language>() == Ok(language) || language.extensions().contains(&tag);
+        if tagged {
+            blocks.push(CodeBlock {
+                start_line: index + 1,
+                info: info.to_string(),
+                code,
+            });
+        }
+    }
+    blocks
+}
+
+// The indentation, fence (three or more backticks or tildes) and info
+// string of a line opening a fenced block
+fn opening_fence(line: &str) -> Option<(usize, &str, &str)> {
+    let trimmed = line.trim_start_matches(' ');
+    let indent = line.len() - trimmed.len();
+    let marker = trimmed.chars().next().filter(|c| *c == '`' || *c == '~')?;
+    let length = trimmed.len() - trimmed.trim_start_matches(marker).len();
+    if indent > 3 || length < 3 {
+        return None;
+    }
+    let (fence, info) = trimmed.split_at(length);
+    // A backtick fence's info string can't hold backticks
+    if marker == '`' && info.contains('`') {
+        return None;
+    }
+    Some((indent, fence, info.trim()))
+}
+
+// At least as many of the opening fence's characters, with nothing after
+fn is_closing_fence(line: &str, fence: &str) -> bool {
+    let trimmed = line.trim_start_matches(' ');
+    let marker = fence.chars().next().unwrap_or('`');
+    let rest = trimmed.trim_start_matches(marker);
+    line.len() - trimmed.len() <= 3
+        && trimmed.len() - rest.len() >= fence.len()
+        && rest.trim().is_empty()
+}
@@ -0,0 +1,340 @@
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+use tree_sitter::Node;
+
+/// 0-based line/column span of an item, plus its byte range for slicing the
+/// original source. Serialized flat, as `start_line`, `start_col`, ...
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Position {
+    pub start_line: usize,
+    pub start_col: usize,
+    pub end_line: usize,
+    pub end_col: usize,
+    pub start_byte: usize,
+    pub end_byte: usize,
+}
+
+impl Position {
+    pub(crate) fn of(node: Node) -> Self {
+        let (start, end) = (node.start_position(), node.end_position());
+        Position {
+            start_line: start.row,
+            start_col: start.column,
+            end_line: end.row,
+            end_col: end.column,
+            start_byte: node.start_byte(),
+            end_byte: node.end_byte(),
+        }
+    }
+}
+
+/// A function, as listed under `functions` (and `nested_functions`).
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FunctionInfo {
+    pub name: String,
+    /// `outer::inner::name`, under `set_module_paths`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub path: Option<String>,
+    /// Declared as a raw identifier, `r#name`
+    pub raw: bool,
+    /// One-line signature, with `where` bounds on type parameters moved
+    /// onto the parameters
+    pub signature: String,
+    pub visibility: String,
+    pub docs: Option<String>,
+    pub is_async: bool,
+    pub is_const: bool,
+    pub is_unsafe: bool,
+    pub extern_abi: Option<String>,
+    pub is_test: bool,
+    pub parameters: Vec<ParameterInfo>,
+    pub return_type: Option<String>,
+    pub return_dispatch_types: Vec<DispatchType>,
+    #[serde(rename = "where")]
+    pub where_predicates: Vec<WherePredicate>,
+    pub lifetimes: Vec<String>,
+    pub control_flow: ControlFlow,
+    pub exits: Exits,
+    /// The body as written; None under `set_exclude_bodies`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub body: Option<String>,
+    pub called_methods: Vec<CallInfo>,
+    pub method_chains: Vec<MethodChain>,
+    pub local_variables: Vec<VariableInfo>,
+    pub nested_functions: Vec<FunctionInfo>,
+    #[serde(flatten)]
+    pub position: Position,
+}
+
+/// One parameter of a function; `self` in any form is named `self`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ParameterInfo {
+    pub name: String,
+    pub raw: bool,
+    #[serde(rename = "type")]
+    pub ty: Option<String>,
+    pub is_mutable: bool,
+    pub is_reference: bool,
+    /// The ABI of an `extern "C" fn(..)` pointer type
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub abi: Option<String>,
+    pub dispatch_types: Vec<DispatchType>,
+    /// The default as written, `1` for Python's `x=1` or JavaScript's
+    /// `x = 1`; Rust parameters have none
+    pub default_value: Option<String>,
+}
+
+/// A `dyn Trait` or `impl Trait` found in a parameter or return type.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DispatchType {
+    /// The trait without its arguments: `Iterator` for `Iterator<Item = u8>`
+    #[serde(rename = "trait")]
+    pub trait_name: String,
+    pub dispatch: Dispatch,
+    /// The whole `dyn ...`/`impl ...` type as written
+    #[serde(rename = "type")]
+    pub ty: String,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Dispatch {
+    /// `dyn Trait`
+    Dynamic,
+    /// `impl Trait`
+    ImplTrait,
+}
+
+/// One `where` clause constraint, `T: Clone + Send`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct WherePredicate {
+    #[serde(rename = "type")]
+    pub ty: String,
+    pub bounds: Vec<String>,
+}
+
+/// A call made in a function body.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CallInfo {
+    /// The callee as written, `self.items.len`
+    pub name: String,
+    #[serde(rename = "type")]
+    pub kind: CallKind,
+    pub receiver: Option<String>,
+    pub path: Vec<String>,
+    /// The function the callee names, `len`
+    pub method: String,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CallKind {
+    Function,
+    Method,
+    Await,
+}
+
+/// Two or more method calls, each made on what the one before returned:
+/// `items.iter().map(f).collect()`. `?` and `.await` between calls don't
+/// break the chain.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct MethodChain {
+    /// What the first call is made on, `items`
+    pub receiver: String,
+    /// The methods in call order, `iter`, `map`, `collect`
+    pub chain: Vec<String>,
+    #[serde(flatten)]
+    pub position: Position,
+}
+
+/// A name bound in a function body, by `let`, `if let`, `while let` or a
+/// match arm.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct VariableInfo {
+    pub name: String,
+    pub kind: BindingKind,
+    pub pattern: String,
+    #[serde(rename = "type")]
+    pub ty: Option<String>,
+    /// The initializer, or the expression being matched
+    pub value: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BindingKind {
+    Let,
+    IfLet,
+    WhileLet,
+    Match,
+}
+
+/// How many of each branching/looping construct a body holds, keyed by the
+/// language's construct names (`if`, `match`, `for`, ...), and how deeply
+/// they nest.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ControlFlow {
+    #[serde(flatten)]
+    pub counts: BTreeMap<String, usize>,
+    pub max_depth: usize,
+}
+
+/// The points where a body leaves the function or a loop early, with how
+/// many there are of each kind. Those inside closures and nested functions
+/// belong to them and aren't counted.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Exits {
+    #[serde(rename = "return")]
+    pub returns: usize,
+    #[serde(rename = "break")]
+    pub breaks: usize,
+    #[serde(rename = "continue")]
+    pub continues: usize,
+    /// `?`
+    #[serde(rename = "try")]
+    pub tries: usize,
+    pub points: Vec<ExitPoint>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ExitPoint {
+    pub kind: ExitKind,
+    #[serde(flatten)]
+    pub position: Position,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ExitKind {
+    Return,
+    Break,
+    Continue,
+    /// `?`
+    Try,
+}
+
+/// A generic parameter with its inline and `where` bounds.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct GenericParam {
+    pub name: String,
+    pub param_kind: GenericKind,
+    /// The declared type of a const generic, `usize` in `const N: usize`
+    #[serde(rename = "type")]
+    pub ty: Option<String>,
+    pub bounds: Vec<String>,
+    pub default: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum GenericKind {
+    Lifetime,
+    Type,
+    Const,
+}
+
+/// A struct, as listed under `structs`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct StructInfo {
+    pub name: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub path: Option<String>,
+    pub raw: bool,
+    pub generics: Vec<GenericParam>,
+    #[serde(rename = "where")]
+    pub where_predicates: Vec<WherePredicate>,
+    pub visibility: String,
+    pub docs: Option<String>,
+    /// Unit, tuple or named; absent for other languages' classes
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub struct_kind: Option<StructKind>,
+    /// The hints of its `#[repr(...)]` attributes, `C` or `packed(2)`
+    pub repr: Vec<String>,
+    pub fields: Vec<FieldInfo>,
+    #[serde(flatten)]
+    pub position: Position,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum StructKind {
+    /// `struct Unit;`
+    Unit,
+    /// `struct Tuple(u32);`
+    Tuple,
+    /// `struct Named { x: u32 }`
+    Named,
+}
+
+/// A field of a struct or enum variant, with its position in the
+/// declaration as `index`. Tuple fields are named by that position.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FieldInfo {
+    pub name: String,
+    pub raw: bool,
+    pub index: usize,
+    #[serde(rename = "type")]
+    pub ty: Option<String>,
+    /// The ABI of an `extern "C" fn(..)` pointer type
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub abi: Option<String>,
+    pub visibility: String,
+    pub attributes: Vec<AttributeInfo>,
+}
+
+/// An enum, as listed under `enums`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct EnumInfo {
+    pub name: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub path: Option<String>,
+    pub generics: Vec<GenericParam>,
+    #[serde(rename = "where")]
+    pub where_predicates: Vec<WherePredicate>,
+    pub visibility: String,
+    pub docs: Option<String>,
+    pub repr: Vec<String>,
+    pub variants: Vec<VariantInfo>,
+    #[serde(flatten)]
+    pub position: Position,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct VariantInfo {
+    pub name: String,
+    pub raw: bool,
+    pub fields: Vec<FieldInfo>,
+    pub discriminant: Option<String>,
+}
+
+/// One node captured by a query passed to `ASTConversionService::query`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CaptureInfo {
+    /// Which of the query's patterns matched, counting from 0
+    pub pattern: usize,
+    /// The capture name without its `@`
+    pub capture: String,
+    /// The captured node's tree-sitter kind
+    pub kind: String,
+    pub text: String,
+    #[serde(flatten)]
+    pub position: Position,
+}
+
+/// One `#[...]` attribute: its full text, its path (`derive`, `serde`) and
+/// its arguments.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AttributeInfo {
+    pub attribute: String,
+    pub path: Option<String>,
+    pub args: Vec<AttributeArg>,
+}
+
+/// `rename = "x"` has the key `rename` and value `x`; `Clone` in
+/// `derive(Clone)` has no key.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AttributeArg {
+    pub key: Option<String>,
+    pub value: String,
+}
@@ -0,0 +1,449 @@
+use serde_json::{json, Value};
+
+/// JSON Schema (draft 2020-12) for the documents the CLI prints: the flat
+/// `generate_json` object, with `--mode tree` a `Thing` tree, with `--flat`
+/// that tree as a `FlatThing` list, with `--stats-only` the `stats` summary,
+/// with `--diff` the `diff` report, or with `--markdown` one of those per
+/// code block.
+pub fn output_schema() -> Value {
+    json!({
+        "$schema": "https://json-schema.org/draft/2020-12/schema",
+        "title": "treesitter output",
+        "anyOf": [
+            { "$ref": "#/$defs/flat" },
+            { "$ref": "#/$defs/thing" },
+            { "$ref": "#/$defs/flat_tree" },
+            { "$ref": "#/$defs/stats" },
+            { "$ref": "#/$defs/diff" },
+            { "$ref": "#/$defs/code_blocks" },
+        ],
+        "$defs": {
+            "flat": {
+                "description": "`--mode flat`: one key per category; `--filter` omits the rest",
+                "type": "object",
+                "properties": {
+                    "docs": { "type": ["string", "null"] },
+                    "imports": items(json!({
+                        "name": string(),
+                        "path": string(),
+                        "alias": nullable_string(),
+                        "is_glob": boolean(),
+                        "declaration": string(),
+                    })),
+                    "functions": refs("function"),
+                    "structs": items(positioned(json!({
+                        "name": string(),
+                        "path": string(),
+                        "raw": boolean(),
+                        "visibility": string(),
+                        "docs": nullable_string(),
+                        "generics": refs("generic"),
+                        "where": refs("where_predicate"),
+                        "struct_kind": { "enum": ["unit", "tuple", "named"] },
+                        "repr": strings(),
+                        "fields": refs("field"),
+                    }))),
+                    "enums": items(positioned(json!({
+                        "name": string(),
+                        "path": string(),
+                        "visibility": string(),
+                        "docs": nullable_string(),
+                        "generics": refs("generic"),
+                        "where": refs("where_predicate"),
+                        "repr": strings(),
+                        "variants": items(json!({
+                            "name": string(),
+                            "raw": boolean(),
+                            "fields": refs("field"),
+                            "discriminant": nullable_string(),
+                        })),
+                    }))),
+                    "traits": items(json!({
+                        "name": string(),
+                        "supertraits": strings(),
+                        "children": items(json!({
+                            "type": { "enum": ["method", "associated_type"] },
+                            "name": string(),
+                            "signature": string(),
+                            "has_default": boolean(),
+                            "bounds": strings(),
+                        })),
+                    })),
+                    "type_aliases": items(json!({
+                        "name": string(),
+                        "type": string(),
+                        "abi": string(),
+                        "generics": nullable_string(),
+                    })),
+                    "relations": items(json!({
+                        "type": { "enum": ["impl", "derive"] },
+                        "traits": strings(),
+                        "details": { "$ref": "#/$defs/attribute" },
+                        "trait": nullable_string(),
+                        "impl_kind": { "enum": ["inherent", "trait"] },
+                        "is_negative": boolean(),
+                        "for": nullable_string(),
+                        "generics": refs("generic"),
+                        "target_generics": refs("type_argument"),
+                        "where": refs("where_predicate"),
+                        "children": items(json!({
+                            "name": string(),
+                            "kind": { "enum": ["method", "associated_fn"] },
+                            "signature": string(),
+                            "visibility": string(),
+                            "receiver": nullable_string(),
+                            "parameters": refs("parameter"),
+                            "return_type": nullable_string(),
+                            "return_dispatch_types": refs("dispatch_type"),
+                            "where": refs("where_predicate"),
+                        })),
+                    })),
+                    "edges": items(json!({
+                        "from": string(),
+                        "to": string(),
+                        "kind": string(),
+                    })),
+                    "call_graph": items(json!({
+                        "from": string(),
+                        "to": string(),
+                    })),
+                    "constants": items(positioned(json!({
+                        "name": string(),
+                        "value": nullable_string(),
+                    }))),
+                    "macros": items(positioned(json!({
+                        "type": { "enum": ["definition", "invocation"] },
+                        "name": string(),
+                        "arguments": nullable_string(),
+                    }))),
+                    "modules_and_impls": items(json!({
+                        "type": string(),
+                        "name": string(),
+                        "docs": nullable_string(),
+                        "is_cfg_test": boolean(),
+                        "children": {
+                            "type": "array",
+                            "items": {
+                                "type": "object",
+                                "description": "a function, struct, enum or module, tagged by `type`",
+                                "properties": { "type": string() },
+                            },
+                        },
+                    })),
+                    "metadata": {
+                        "type": "object",
+                        "properties": {
+                            "attributes": refs("attribute"),
+                            "has_errors": boolean(),
+                            "total_lines": count(),
+                            "function_count": count(),
+                            "struct_count": count(),
+                            "enum_count": count(),
+                            "parse_error_count": count(),
+                        },
+                    },
+                    "errors": items(positioned(json!({
+                        "type": { "enum": ["error", "missing"] },
+                        "kind": string(),
+                        "text": string(),
+                        "context": string(),
+                    }))),
+                    "nested_items": refs("nested_item"),
+                    "globals": items(json!({
+                        "name": string(),
+                        "type": nullable_string(),
+                        "value": string(),
+                    })),
+                    "schemas": items(json!({
+                        "struct": string(),
+                        "derives": strings(),
+                        "attributes": refs("attribute"),
+                        "fields": refs("field"),
+                        "relationships": items(json!({
+                            "field": string(),
+                            "relationship": string(),
+                        })),
+                    })),
+                    "literals": items(positioned(json!({
+                        "type": { "enum": ["string", "integer", "float", "number", "boolean"] },
+                        "value": string(),
+                        "raw_hashes": { "type": ["integer", "null"], "minimum": 0 },
+                    }))),
+                    "tests": items(positioned(json!({
+                        "name": string(),
+                        "module": nullable_string(),
+                    }))),
+                    "foreign_items": items(positioned(json!({
+                        "abi": string(),
+                        "items": refs("foreign_item"),
+                    }))),
+                },
+            },
+            "stats": {
+                "description": "`--stats-only`: item counts per list category",
+                "type": "object",
+                "required": ["counts", "total_lines"],
+                "properties": {
+                    "counts": {
+                        "type": "object",
+                        "additionalProperties": count(),
+                    },
+                    "total_lines": count(),
+                    "has_errors": boolean(),
+                    "parse_error_count": count(),
+                },
+            },
+            "diff": {
+                "description": "`--diff`: top-level items that differ between two files",
+                "type": "object",
+                "required": ["added", "removed", "changed"],
+                "properties": {
+                    "added": refs("diffed_item"),
+                    "removed": refs("diffed_item"),
+                    "changed": refs("diffed_item"),
+                },
+            },
+            "code_blocks": {
+                "description": "`--markdown`: one entry per fenced code block",
+                "type": "array",
+                "items": {
+                    "type": "object",
+                    "required": ["start_line"],
+                    "properties": {
+                        "start_line": count(),
+                        "ast": {
+                            "anyOf": [
+                                { "$ref": "#/$defs/flat" },
+                                { "$ref": "#/$defs/thing" },
+                                { "$ref": "#/$defs/flat_tree" },
+                                { "$ref": "#/$defs/stats" },
+                            ],
+                        },
+                        "error": string(),
+                    },
+                },
+            },
+            "diffed_item": object(json!({
+                "category": string(),
+                "name": string(),
+                "before": { "type": ["string", "array"] },
+                "after": { "type": ["string", "array"] },
+            })),
+            "function": function(),
+            "parameter": object(json!({
+                "name": string(),
+                "raw": boolean(),
+                "type": nullable_string(),
+                "is_mutable": boolean(),
+                "is_reference": boolean(),
+                "abi": string(),
+                "dispatch_types": refs("dispatch_type"),
+                "default_value": nullable_string(),
+            })),
+            "dispatch_type": object(json!({
+                "trait": string(),
+                "dispatch": { "enum": ["dynamic", "impl_trait"] },
+                "type": string(),
+            })),
+            "generic": object(json!({
+                "name": string(),
+                "param_kind": { "enum": ["lifetime", "type", "const"] },
+                "type": nullable_string(),
+                "bounds": strings(),
+                "default": nullable_string(),
+            })),
+            "type_argument": object(json!({
+                "kind": { "enum": ["type", "lifetime", "const", "binding"] },
+                "name": nullable_string(),
+                "value": string(),
+                "is_param": boolean(),
+            })),
+            "exit_point": object(positioned(json!({
+                "kind": { "enum": ["return", "break", "continue", "try"] },
+            }))),
+            "where_predicate": object(json!({
+                "type": string(),
+                "bounds": strings(),
+            })),
+            "field": object(json!({
+                "name": string(),
+                "raw": boolean(),
+                "index": count(),
+                "type": nullable_string(),
+                "abi": string(),
+                "visibility": string(),
+                "attributes": refs("attribute"),
+            })),
+            "attribute": object(json!({
+                "attribute": string(),
+                "path": nullable_string(),
+                "args": items(json!({
+                    "key": nullable_string(),
+                    "value": string(),
+                })),
+            })),
+            "foreign_item": object(positioned(json!({
+                "kind": { "enum": ["function", "static", "type"] },
+                "name": string(),
+                "visibility": string(),
+                "docs": nullable_string(),
+                "parameters": refs("parameter"),
+                "is_variadic": boolean(),
+                "return_type": nullable_string(),
+                "type": nullable_string(),
+                "is_mutable": boolean(),
+                "signature": string(),
+            }))),
+            "nested_item": object(json!({
+                "type": string(),
+                "name": string(),
+                "children": refs("nested_item"),
+            })),
+            "thing": {
+                "description": "`--mode tree`: one node of the recursive tree",
+                "type": "object",
+                "required": ["kind", "text"],
+                "properties": {
+                    "kind": { "$ref": "#/$defs/kind" },
+                    "name": string(),
+                    "text": string(),
+                    "condition": string(),
+                    "raw_kind": string(),
+                    "truncated": boolean(),
+                    "children": refs("thing"),
+                    "relations": strings(),
+                },
+            },
+            "flat_tree": {
+                "description": "`--flat`: the tree's nodes in pre-order, linked by `parent_id`",
+                "type": "array",
+                "items": {
+                    "type": "object",
+                    "required": ["id", "parent_id", "kind", "text"],
+                    "properties": {
+                        "id": count(),
+                        "parent_id": { "type": ["integer", "null"], "minimum": 0 },
+                        "kind": { "$ref": "#/$defs/kind" },
+                        "name": string(),
+                        "text": string(),
+                        "condition": string(),
+                        "raw_kind": string(),
+                        "truncated": boolean(),
+                        "relations": strings(),
+                    },
+                },
+            },
+            "kind": {
+                "enum": [
+                    "Root", "Comment", "DocComment", "Import", "Module", "Struct", "Enum",
+                    "Derive", "Function", "Method", "Field", "Variant", "Variable",
+                    "Type", "Trait", "Impl", "If", "Else", "Loop", "Tuple", "Array",
+                    "FunctionCall", "Undefined",
+                ],
+            },
+        },
+    })
+}
+
+fn function() -> Value {
+    object(positioned(json!({
+        "name": string(),
+        "path": string(),
+        "raw": boolean(),
+        "signature": string(),
+        "visibility": string(),
+        "docs": nullable_string(),
+        "is_async": boolean(),
+        "is_const": boolean(),
+        "is_unsafe": boolean(),
+        "extern_abi": nullable_string(),
+        "is_test": boolean(),
+        "parameters": refs("parameter"),
+        "return_type": nullable_string(),
+        "return_dispatch_types": refs("dispatch_type"),
+        "where": refs("where_predicate"),
+        "lifetimes": strings(),
+        "control_flow": {
+            "type": "object",
+            "properties": { "max_depth": count() },
+            "additionalProperties": count(),
+        },
+        "exits": {
+            "type": "object",
+            "properties": {
+                "return": count(),
+                "break": count(),
+                "continue": count(),
+                "try": count(),
+                "points": refs("exit_point"),
+            },
+        },
+        "body": nullable_string(),
+        "called_methods": items(json!({
+            "name": string(),
+            "type": { "enum": ["function", "method", "await"] },
+            "receiver": nullable_string(),
+            "path": strings(),
+            "method": string(),
+        })),
+        "method_chains": items(positioned(json!({
+            "receiver": string(),
+            "chain": strings(),
+        }))),
+        "local_variables": items(json!({
+            "name": string(),
+            "kind": { "enum": ["let", "if_let", "while_let", "match"] },
+            "pattern": string(),
+            "type": nullable_string(),
+            "value": nullable_string(),
+        })),
+        "nested_functions": refs("function"),
+    })))
+}
+
+// Add the line/column/byte span that `with_position` attaches
+fn positioned(mut properties: Value) -> Value {
+    for key in [
+        "start_line",
+        "start_col",
+        "end_line",
+        "end_col",
+        "start_byte",
+        "end_byte",
+    ] {
+        properties[key] = json!({ "type": "integer", "minimum": 0 });
+    }
+    properties
+}
+
+fn object(properties: Value) -> Value {
+    json!({ "type": "object", "properties": properties })
+}
+
+fn items(properties: Value) -> Value {
+    json!({ "type": "array", "items": object(properties) })
+}
+
+fn refs(def: &str) -> Value {
+    json!({ "type": "array", "items": { "$ref": format!("#/$defs/{}", def) } })
+}
+
+fn string() -> Value {
+    json!({ "type": "string" })
+}
+
+fn nullable_string() -> Value {
+    json!({ "type": ["string", "null"] })
+}
+
+fn strings() -> Value {
+    json!({ "type": "array", "items": string() })
+}
+
+fn count() -> Value {
+    json!({ "type": "integer", "minimum": 0 })
+}
+
+fn boolean() -> Value {
+    json!({ "type": "boolean" })
+}
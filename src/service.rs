@@ -0,0 +1,2840 @@
+use std::borrow::Cow;
+use std::collections::HashSet;
+use std::ops::Range;
+use std::str::FromStr;
+use std::time::Duration;
+
+use serde::Serialize;
+use serde_json::{json, Map, Value};
+use streaming_iterator::StreamingIterator;
+use tree_sitter::{InputEdit, Node, Parser, Query, QueryCursor, Tree};
+
+use crate::extractor::{Extractor, BUILTINS};
+use crate::language::NodeKinds;
+use crate::model::{
+    AttributeArg, AttributeInfo, BindingKind, CallInfo, CallKind, CaptureInfo, ControlFlow,
+    Dispatch, DispatchType, EnumInfo, ExitKind, ExitPoint, Exits, FieldInfo, FunctionInfo,
+    GenericKind, GenericParam, MethodChain, ParameterInfo, Position, StructInfo, StructKind,
+    VariableInfo, VariantInfo, WherePredicate,
+};
+use crate::{ConversionError, Language};
+
+/// Top-level keys of the `generate_json` object, in extraction order.
+pub const CATEGORIES: &[&str] = &[
+    "docs",
+    "imports",
+    "functions",
+    "structs",
+    "enums",
+    "traits",
+    "type_aliases",
+    "relations",
+    "edges",
+    "call_graph",
+    "constants",
+    "macros",
+    "modules_and_impls",
+    "metadata",
+    "errors",
+    "nested_items",
+    "globals",
+    "schemas",
+    "literals",
+    "tests",
+    "foreign_items",
+];
+
+/// One top-level item in the flat table built by `to_symbol_table`.
+#[derive(Debug, Serialize)]
+pub struct SymbolRow {
+    pub kind: String,
+    pub name: String,
+    pub visibility: Option<String>,
+    pub start_line: usize,
+    pub end_line: usize,
+}
+
+/// Which items `set_visibility_filter` keeps.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum VisibilityFilter {
+    /// Everything, whatever its visibility
+    #[default]
+    All,
+    /// Only `pub` items
+    Public,
+    /// `pub` and `pub(crate)` items
+    PublicAndCrate,
+}
+
+impl VisibilityFilter {
+    fn keeps(self, visibility: &str) -> bool {
+        match self {
+            VisibilityFilter::All => true,
+            VisibilityFilter::Public => visibility == "pub",
+            VisibilityFilter::PublicAndCrate => visibility == "pub" || visibility == "pub(crate)",
+        }
+    }
+}
+
+/// How `set_sort_order` orders the items of each category.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum SortOrder {
+    /// The order they appear in the source
+    #[default]
+    Source,
+    /// By name
+    Name,
+    /// By their `type` or `kind` tag, then by name
+    Kind,
+}
+
+// What an item is sorted by when it has no `name`: an edge by its source,
+// a relation by its target type, a schema by its struct, a literal by its
+// value
+const SORT_NAME_KEYS: &[&str] = &["name", "from", "for", "struct", "value"];
+const SORT_KIND_KEYS: &[&str] = &["type", "kind"];
+
+impl SortOrder {
+    // Stable, so items with equal keys keep their source order and the
+    // result only depends on the input
+    fn sort(self, items: &mut [Value]) {
+        let key = |item: &Value, keys: &[&str]| -> String {
+            keys.iter()
+                .find_map(|key| item.get(*key).and_then(Value::as_str))
+                .unwrap_or_default()
+                .to_string()
+        };
+        match self {
+            SortOrder::Source => {}
+            SortOrder::Name => items.sort_by_cached_key(|item| key(item, SORT_NAME_KEYS)),
+            SortOrder::Kind => items
+                .sort_by_cached_key(|item| (key(item, SORT_KIND_KEYS), key(item, SORT_NAME_KEYS))),
+        }
+    }
+}
+
+impl FromStr for SortOrder {
+    type Err = String;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "source" => Ok(SortOrder::Source),
+            "name" => Ok(SortOrder::Name),
+            "kind" => Ok(SortOrder::Kind),
+            _ => Err(format!(
+                "unknown sort order `{}` (expected source, name or kind)",
+                s
+            )),
+        }
+    }
+}
+
+/// Parses a Rust source file once and extracts its items as JSON.
+pub struct ASTConversionService {
+    code: String,
+    language: Language,
+    parser: Parser,
+    pub(crate) tree: Tree,
+    strip_comments: bool,
+    visibility_filter: VisibilityFilter,
+    exclude_bodies: bool,
+    module_paths: bool,
+    sort_order: SortOrder,
+    extractors: Vec<Box<dyn Extractor>>,
+}
+
+impl ASTConversionService {
+    pub fn new(code: String) -> Result<Self, ConversionError> {
+        Self::with_language(code, Language::Rust)
+    }
+    /// Parse `code` with the grammar of `language` instead of Rust.
+    pub fn with_language(code: String, language: Language) -> Result<Self, ConversionError> {
+        Self::with_timeout(code, language, None)
+    }
+    /// Like `with_language`, but give up with `ConversionError::Timeout`
+    /// when parsing (here or in `apply_edit`) takes longer than `timeout`.
+    /// Without one, parsing is never cut short.
+    pub fn with_timeout(
+        code: String,
+        language: Language,
+        timeout: Option<Duration>,
+    ) -> Result<Self, ConversionError> {
+        let mut parser = Parser::new();
+        parser.set_language(&language.grammar())?;
+        if let Some(timeout) = timeout {
+            parser.set_timeout_micros(timeout.as_micros().max(1) as u64);
+        }
+        let tree = parser
+            .parse(&code, None)
+            .ok_or_else(|| Self::parse_error(&parser, code.len()))?;
+        Ok(ASTConversionService {
+            code,
+            language,
+            parser,
+            tree,
+            strip_comments: false,
+            visibility_filter: VisibilityFilter::All,
+            exclude_bodies: false,
+            module_paths: false,
+            sort_order: SortOrder::Source,
+            extractors: Vec::new(),
+        })
+    }
+    /// Emit function bodies without their comments, for diffs that should
+    /// ignore comment churn. Bodies are verbatim by default.
+    pub fn set_strip_comments(&mut self, strip: bool) {
+        self.strip_comments = strip;
+    }
+    /// Leave out functions, structs, enums, fields and inherent methods the
+    /// filter doesn't keep, for extracting just the public API. Trait impl
+    /// methods are kept, since they are as visible as the trait.
+    pub fn set_visibility_filter(&mut self, filter: VisibilityFilter) {
+        self.visibility_filter = filter;
+    }
+    /// Leave out function bodies and macro invocation arguments, keeping
+    /// names, signatures and structure, for much smaller output.
+    pub fn set_exclude_bodies(&mut self, exclude: bool) {
+        self.exclude_bodies = exclude;
+    }
+    /// Give functions, structs and enums a `path` through the modules the
+    /// file declares, `outer::inner::Item`. The file's own module
+    /// contributes no prefix, so in a crate root this is the path after
+    /// `crate::`. Functions inside a body have no path.
+    pub fn set_module_paths(&mut self, module_paths: bool) {
+        self.module_paths = module_paths;
+    }
+    /// Order the items of every list category in `generate_json` by name
+    /// or kind instead of source order, for output that diffs cleanly when
+    /// items move around. Nested lists (fields, methods) keep source order.
+    pub fn set_sort_order(&mut self, order: SortOrder) {
+        self.sort_order = order;
+    }
+    /// Emit `extractor`'s category in `generate_json` and `stats`, after
+    /// the built-in ones, or in place of the built-in category with the
+    /// same key. A later extractor with the same key replaces an earlier one.
+    pub fn add_extractor(&mut self, extractor: impl Extractor + 'static) {
+        self.extractors.push(Box::new(extractor));
+    }
+    /// Re-parse after an edit, reusing the unchanged parts of the old tree.
+    ///
+    /// `edit` describes how the previous source was turned into `new_code`.
+    pub fn apply_edit(&mut self, edit: InputEdit, new_code: String) -> Result<(), ConversionError> {
+        self.tree.edit(&edit);
+        let Some(tree) = self.parser.parse(&new_code, Some(&self.tree)) else {
+            let err = Self::parse_error(&self.parser, new_code.len());
+            // Otherwise the next parse would resume the abandoned one
+            self.parser.reset();
+            return Err(err);
+        };
+        self.code = new_code;
+        self.tree = tree;
+        Ok(())
+    }
+    // tree-sitter only gives up without a tree when it runs out of time
+    fn parse_error(parser: &Parser, input_len: usize) -> ConversionError {
+        match parser.timeout_micros() {
+            0 => ConversionError::ParseFailed { input_len },
+            micros => ConversionError::Timeout {
+                input_len,
+                timeout: Duration::from_micros(micros),
+            },
+        }
+    }
+    /// Extract every category into one JSON object.
+    ///
+    /// `structs` lists every struct with its fields, while `schemas` only
+    /// covers structs deriving serde's `Serialize`/`Deserialize`, i.e. the
+    /// types that describe a serialized data format. Categories added by
+    /// `add_extractor` follow the built-in ones.
+    pub fn generate_json(&self) -> Value {
+        self.generate_json_for(&self.category_keys())
+    }
+    /// Like `generate_json`, but only extracts the listed top-level keys.
+    /// Names that are neither in `CATEGORIES` nor an added extractor's key
+    /// are ignored.
+    pub fn generate_json_for(&self, categories: &[&str]) -> Value {
+        let builtin_metadata = self.added_extractor("metadata").is_none();
+        let mut object = Map::new();
+        for category in categories {
+            if *category == "metadata" && builtin_metadata {
+                continue;
+            }
+            if let Some(mut value) = self.extract_category(category) {
+                if let Value::Array(items) = &mut value {
+                    self.sort_order.sort(items);
+                }
+                object.insert(category.to_string(), value);
+            }
+        }
+        // Last, so its counts can reuse the lists extracted above
+        if categories.contains(&"metadata") && builtin_metadata {
+            let metadata = self.build_metadata(&object);
+            object.insert("metadata".to_string(), metadata);
+        }
+        Value::Object(object)
+    }
+    /// A summary of `generate_json`: how many items each list category
+    /// holds, plus the line and parse error counts from `metadata`.
+    pub fn stats(&self) -> Value {
+        self.stats_for(&self.category_keys())
+    }
+    /// Like `stats`, but only counts the listed categories.
+    pub fn stats_for(&self, categories: &[&str]) -> Value {
+        let mut extracted = Map::new();
+        for category in categories {
+            if let Some(value @ Value::Array(_)) = self.extract_category(category) {
+                extracted.insert(category.to_string(), value);
+            }
+        }
+        let counts: Map<String, Value> = extracted
+            .keys()
+            .map(|category| {
+                let count = self.category_count(&extracted, category);
+                (category.clone(), json!(count))
+            })
+            .collect();
+        json!({
+            "counts": counts,
+            "total_lines": self.code.lines().count(),
+            "has_errors": self.tree.root_node().has_error(),
+            "parse_error_count": self.category_count(&extracted, "errors"),
+        })
+    }
+    /// The file's inner (`//!`) doc comments, joined.
+    pub fn docs(&self) -> Option<String> {
+        self.extract_inner_docs(self.tree.root_node())
+    }
+    /// `use` declarations, one entry per imported path.
+    pub fn imports(&self) -> Vec<Value> {
+        self.extract_imports(self.tree.root_node())
+    }
+    /// Top-level functions.
+    pub fn functions(&self) -> Vec<FunctionInfo> {
+        self.functions_in(self.tree.root_node())
+    }
+    /// Top-level structs with their fields.
+    pub fn structs(&self) -> Vec<StructInfo> {
+        self.visible_structs(self.tree.root_node())
+    }
+    /// Top-level enums with their variants.
+    pub fn enums(&self) -> Vec<EnumInfo> {
+        self.visible_enums(self.tree.root_node())
+    }
+    /// Trait definitions with their items.
+    pub fn traits(&self) -> Vec<Value> {
+        self.extract_traits(self.tree.root_node())
+    }
+    /// `type` aliases.
+    pub fn type_aliases(&self) -> Vec<Value> {
+        self.extract_type_aliases(self.tree.root_node())
+    }
+    /// `impl` blocks with the type, trait and methods involved, and
+    /// `#[derive]` attributes with the type they derive for.
+    pub fn relations(&self) -> Vec<Value> {
+        let mut relations = self.extract_relations(self.tree.root_node());
+        for relation in &mut relations {
+            if relation["type"] == "impl" && relation["trait"].is_null() {
+                self.retain_visible_in(relation, "children");
+            }
+        }
+        relations
+    }
+    /// The impl relations as `{ from, to, kind }` graph edges.
+    pub fn edges(&self) -> Vec<Value> {
+        let mut edges = self.extract_edges(self.tree.root_node());
+        // Edges touching a type `visibility_filter` leaves out go with it
+        let mut types = Vec::new();
+        self.collect_local_types(self.tree.root_node(), &mut types);
+        let hidden: Vec<&str> = types
+            .into_iter()
+            .filter(|(_, _, kept)| !kept)
+            .map(|(name, _, _)| name)
+            .collect();
+        edges.retain(|edge| {
+            let ends = [&edge["from"], &edge["to"]];
+            !ends
+                .iter()
+                .any(|end| hidden.contains(&end.as_str().unwrap_or_default()))
+        });
+        edges
+    }
+    /// `(caller, callee)` pairs for each call, from any function or method,
+    /// to a function or method defined in this file. Calls are matched by
+    /// the function they name, so `self.len()` and `Self::len()` both reach
+    /// a `fn len` here.
+    pub fn call_edges(&self) -> Vec<(String, String)> {
+        let mut functions = Vec::new();
+        self.collect_functions(self.tree.root_node(), &mut functions);
+        let named: Vec<(&str, Node)> = functions
+            .into_iter()
+            .filter(|function| self.keeps_item(*function))
+            .filter_map(|function| {
+                let name_node = function.child_by_field_name("name")?;
+                let (name, _) = Self::identifier_name(self.node_text(name_node));
+                Some((name, function))
+            })
+            .collect();
+        let defined: HashSet<&str> = named.iter().map(|(name, _)| *name).collect();
+        let mut edges = Vec::new();
+        for (caller, function) in &named {
+            for call in self.extract_called_methods(*function) {
+                let edge = (caller.to_string(), call.method);
+                if defined.contains(edge.1.as_str()) && !edges.contains(&edge) {
+                    edges.push(edge);
+                }
+            }
+        }
+        edges
+    }
+    /// `const` items.
+    pub fn constants(&self) -> Vec<Value> {
+        self.extract_constants(self.tree.root_node())
+    }
+    /// Macro definitions and invocations.
+    pub fn macros(&self) -> Vec<Value> {
+        self.without_bodies(self.extract_macros(self.tree.root_node()), "arguments")
+    }
+    /// Modules and impls with the items declared inside them.
+    pub fn modules_and_impls(&self) -> Vec<Value> {
+        self.extract_modules_and_impls(self.tree.root_node())
+    }
+    /// Top-level attributes, line and item counts, and whether the file
+    /// parsed cleanly.
+    pub fn metadata(&self) -> Value {
+        self.build_metadata(&Map::new())
+    }
+    /// Syntax errors tree-sitter recovered from.
+    pub fn errors(&self) -> Vec<Value> {
+        self.extract_errors(self.tree.root_node())
+    }
+    /// Every named item as a tree of `{ type, name, children }`.
+    pub fn nested_items(&self) -> Vec<Value> {
+        self.extract_nested(self.tree.root_node())
+    }
+    /// `static` items.
+    pub fn globals(&self) -> Vec<Value> {
+        self.extract_globals(self.tree.root_node())
+    }
+    /// Structs deriving serde's `Serialize`/`Deserialize`.
+    pub fn schemas(&self) -> Vec<Value> {
+        self.extract_schema(self.tree.root_node())
+    }
+    /// Every literal in the file, in source order.
+    pub fn literals(&self) -> Vec<Value> {
+        self.extract_literals(self.tree.root_node())
+    }
+    /// `#[test]` functions anywhere in the file, with the module they're in.
+    pub fn tests(&self) -> Vec<Value> {
+        self.extract_tests(self.tree.root_node(), None)
+    }
+    /// `extern` blocks with their ABI and the foreign functions, statics
+    /// and types they declare.
+    pub fn foreign_items(&self) -> Vec<Value> {
+        self.extract_foreign_items(self.tree.root_node())
+    }
+    // Remove the verbatim `key` from each item under `exclude_bodies`
+    fn without_bodies(&self, mut items: Vec<Value>, key: &str) -> Vec<Value> {
+        if self.exclude_bodies {
+            for item in &mut items {
+                if let Some(object) = item.as_object_mut() {
+                    object.remove(key);
+                }
+            }
+        }
+        items
+    }
+    fn clear_bodies(function: &mut FunctionInfo) {
+        function.body = None;
+        for nested in &mut function.nested_functions {
+            Self::clear_bodies(nested);
+        }
+    }
+    // The functions directly in `node` as `functions` reports them, filtered
+    // and without bodies under `exclude_bodies`
+    fn functions_in(&self, node: Node) -> Vec<FunctionInfo> {
+        let mut functions = self.visible_functions(node);
+        if self.exclude_bodies {
+            for function in &mut functions {
+                Self::clear_bodies(function);
+            }
+        }
+        functions
+    }
+    // The functions, structs and enums directly in `node` that
+    // `visibility_filter` keeps
+    fn visible_functions(&self, node: Node) -> Vec<FunctionInfo> {
+        let filter = self.visibility_filter;
+        let mut functions = self.extract_functions(node);
+        functions.retain(|function| filter.keeps(&function.visibility));
+        // Functions inside a body are kept or dropped by their own visibility
+        for function in &mut functions {
+            function
+                .nested_functions
+                .retain(|nested| filter.keeps(&nested.visibility));
+        }
+        functions
+    }
+    fn visible_structs(&self, node: Node) -> Vec<StructInfo> {
+        let filter = self.visibility_filter;
+        let mut structs = self.extract_structs(node);
+        structs.retain(|item| filter.keeps(&item.visibility));
+        for item in &mut structs {
+            item.fields.retain(|field| filter.keeps(&field.visibility));
+        }
+        structs
+    }
+    fn visible_enums(&self, node: Node) -> Vec<EnumInfo> {
+        let filter = self.visibility_filter;
+        let mut enums = self.extract_enums(node);
+        enums.retain(|item| filter.keeps(&item.visibility));
+        enums
+    }
+    // Whether `visibility_filter` keeps an item node. Impls have no
+    // visibility of their own, and trait items and the methods of a trait
+    // impl are as visible as the trait, so those are always kept.
+    fn keeps_item(&self, item_node: Node) -> bool {
+        let kinds = self.kinds();
+        if kinds.impls.contains(&item_node.kind()) {
+            return true;
+        }
+        let owner = item_node.parent().and_then(|list| list.parent());
+        let in_trait = owner.is_some_and(|owner| {
+            kinds.traits.contains(&owner.kind())
+                || (kinds.impls.contains(&owner.kind())
+                    && owner.child_by_field_name("trait").is_some())
+        });
+        in_trait
+            || self
+                .visibility_filter
+                .keeps(&self.extract_visibility(item_node))
+    }
+    // Drop the items `visibility_filter` excludes
+    fn retain_visible(&self, mut items: Vec<Value>) -> Vec<Value> {
+        let filter = self.visibility_filter;
+        items.retain(|item| filter.keeps(item["visibility"].as_str().unwrap_or("private")));
+        items
+    }
+    fn retain_visible_in(&self, item: &mut Value, key: &str) {
+        if let Some(Value::Array(children)) = item.get_mut(key) {
+            *children = self.retain_visible(std::mem::take(children));
+        }
+    }
+    // Counts come from the categories in `extracted` when present, so
+    // `generate_json` doesn't walk the tree a second time for them
+    fn build_metadata(&self, extracted: &Map<String, Value>) -> Value {
+        let count = |category: &str| self.category_count(extracted, category);
+        let root_node = self.tree.root_node();
+        json!({
+            "attributes": self.extract_metadata(root_node),
+            "has_errors": root_node.has_error(),
+            "total_lines": self.code.lines().count(),
+            "function_count": count("functions"),
+            "struct_count": count("structs"),
+            "enum_count": count("enums"),
+            "parse_error_count": count("errors"),
+        })
+    }
+    // How many items `category` holds, from `extracted` when it's there
+    fn category_count(&self, extracted: &Map<String, Value>, category: &str) -> usize {
+        match extracted.get(category) {
+            Some(Value::Array(items)) => items.len(),
+            _ => match self.extract_category(category) {
+                Some(Value::Array(items)) => items.len(),
+                _ => 0,
+            },
+        }
+    }
+    fn extract_category(&self, category: &str) -> Option<Value> {
+        let root_node = self.tree.root_node();
+        if let Some(extractor) = self.added_extractor(category) {
+            return Some(extractor.extract(self, root_node));
+        }
+        let builtin = BUILTINS.iter().find(|builtin| builtin.key() == category)?;
+        Some(builtin.extract(self, root_node))
+    }
+    fn added_extractor(&self, key: &str) -> Option<&dyn Extractor> {
+        let extractor = self.extractors.iter().rev().find(|e| e.key() == key)?;
+        Some(extractor.as_ref())
+    }
+    // `CATEGORIES`, then the keys of added extractors that aren't among them
+    fn category_keys(&self) -> Vec<&str> {
+        let mut keys = CATEGORIES.to_vec();
+        for extractor in &self.extractors {
+            if !keys.contains(&extractor.key()) {
+                keys.push(extractor.key());
+            }
+        }
+        keys
+    }
+    /// Run a tree-sitter query, `(function_item name: (identifier) @name)`,
+    /// against the parsed tree: every capture of every match, in the order
+    /// the matches are found. The tree is reused, so any number of queries
+    /// can be run without re-parsing.
+    pub fn query(&self, source: &str) -> Result<Vec<CaptureInfo>, ConversionError> {
+        let query = Query::new(&self.language.grammar(), source)?;
+        let names = query.capture_names();
+        let mut cursor = QueryCursor::new();
+        let mut matches = cursor.matches(&query, self.tree.root_node(), self.code.as_bytes());
+        let mut captures = Vec::new();
+        while let Some(found) = matches.next() {
+            for capture in found.captures {
+                captures.push(CaptureInfo {
+                    pattern: found.pattern_index,
+                    capture: names[capture.index as usize].to_string(),
+                    kind: capture.node.kind().to_string(),
+                    text: self.node_text(capture.node).to_string(),
+                    position: Position::of(capture.node),
+                });
+            }
+        }
+        Ok(captures)
+    }
+    /// The syntax tree as tree-sitter's s-expression, with field names:
+    /// exactly what the extractors walk.
+    pub fn to_sexp(&self) -> String {
+        self.tree.root_node().to_sexp()
+    }
+    /// Number of nodes in the syntax tree, anonymous tokens included.
+    pub fn node_count(&self) -> usize {
+        let mut cursor = self.tree.walk();
+        let mut count = 1;
+        loop {
+            if cursor.goto_first_child() || cursor.goto_next_sibling() {
+                count += 1;
+                continue;
+            }
+            // Climb until some ancestor has a next sibling, or we're back at
+            // the root
+            loop {
+                if !cursor.goto_parent() {
+                    return count;
+                }
+                if cursor.goto_next_sibling() {
+                    count += 1;
+                    break;
+                }
+            }
+        }
+    }
+    /// Flatten the top-level functions, structs, enums, traits, type
+    /// aliases, constants and statics into one row per item, ordered by
+    /// position in the source. Only the items `set_visibility_filter` keeps
+    /// get a row.
+    pub fn to_symbol_table(&self) -> Vec<SymbolRow> {
+        let root_node = self.tree.root_node();
+        let row =
+            |kind: &str, name: &str, visibility: Option<&str>, position: Position| SymbolRow {
+                kind: kind.to_string(),
+                name: name.to_string(),
+                visibility: visibility.map(String::from),
+                start_line: position.start_line,
+                end_line: position.end_line,
+            };
+        let mut rows = Vec::new();
+        for function in self.visible_functions(root_node) {
+            let visibility = Some(function.visibility.as_str());
+            rows.push(row(
+                "function",
+                &function.name,
+                visibility,
+                function.position,
+            ));
+        }
+        for item in self.visible_structs(root_node) {
+            rows.push(row(
+                "struct",
+                &item.name,
+                Some(&item.visibility),
+                item.position,
+            ));
+        }
+        for item in self.visible_enums(root_node) {
+            rows.push(row(
+                "enum",
+                &item.name,
+                Some(&item.visibility),
+                item.position,
+            ));
+        }
+        // Their extractors report no visibility or position, so they're
+        // read off the item nodes
+        let kinds = self.kinds();
+        let other_items = [
+            ("trait", kinds.traits),
+            ("type_alias", kinds.type_aliases),
+            ("constant", kinds.constants),
+            ("static", kinds.globals),
+        ];
+        for child in root_node.children(&mut root_node.walk()) {
+            let Some((kind, _)) = other_items
+                .iter()
+                .find(|(_, item_kinds)| item_kinds.contains(&child.kind()))
+            else {
+                continue;
+            };
+            let Some(name_node) = child.child_by_field_name("name") else {
+                continue;
+            };
+            let visibility = self.extract_visibility(child);
+            if !self.visibility_filter.keeps(&visibility) {
+                continue;
+            }
+            rows.push(row(
+                kind,
+                Self::identifier_name(self.node_text(name_node)).0,
+                Some(&visibility),
+                Position::of(child),
+            ));
+        }
+        rows.sort_by_key(|row| row.start_line);
+        rows
+    }
+    /// Render structs, enums and traits, in modules too, with their
+    /// `implements`/`derives` edges as a Graphviz DOT digraph. Edge endpoints
+    /// not defined in this file (e.g. std traits) are drawn dashed.
+    pub fn to_dot(&self) -> String {
+        let root_node = self.tree.root_node();
+        let quote = |name: &str| {
+            let escaped = name.replace('\\', "\\\\").replace('"', "\\\"");
+            format!("\"{}\"", escaped)
+        };
+        let mut types = Vec::new();
+        self.collect_local_types(root_node, &mut types);
+        let nodes: Vec<(&str, &str)> = types
+            .into_iter()
+            .filter(|(_, _, kept)| *kept)
+            .map(|(name, shape, _)| (name, shape))
+            .collect();
+        let edges = self.edges();
+        let mut dot = String::from("digraph relations {\n");
+        for (name, shape) in &nodes {
+            dot.push_str(&format!(
+                "    {} [label={}, shape={}];\n",
+                quote(name),
+                quote(name),
+                shape
+            ));
+        }
+        let mut external: Vec<&str> = Vec::new();
+        for edge in &edges {
+            for end in [&edge["from"], &edge["to"]] {
+                let name = end.as_str().unwrap_or_default();
+                if !nodes.iter().any(|(known, _)| *known == name) && !external.contains(&name) {
+                    external.push(name);
+                }
+            }
+        }
+        for name in external {
+            dot.push_str(&format!(
+                "    {} [label={}, style=dashed];\n",
+                quote(name),
+                quote(name)
+            ));
+        }
+        for edge in &edges {
+            dot.push_str(&format!(
+                "    {} -> {} [label={}];\n",
+                quote(edge["from"].as_str().unwrap_or_default()),
+                quote(edge["to"].as_str().unwrap_or_default()),
+                quote(edge["kind"].as_str().unwrap_or_default())
+            ));
+        }
+        dot.push('}');
+        dot
+    }
+    // The structs, enums and traits declared in `node` and its modules, by
+    // the same bare names as the edges, with the shape `to_dot` draws them
+    // in and whether `visibility_filter` keeps them
+    fn collect_local_types<'s>(
+        &'s self,
+        node: Node,
+        types: &mut Vec<(&'s str, &'static str, bool)>,
+    ) {
+        let kinds = self.kinds();
+        for child in node.children(&mut node.walk()) {
+            let shape =
+                if kinds.structs.contains(&child.kind()) || kinds.enums.contains(&child.kind()) {
+                    "box"
+                } else if kinds.traits.contains(&child.kind()) {
+                    "ellipse"
+                } else {
+                    if kinds.modules.contains(&child.kind()) {
+                        if let Some(body) = child.child_by_field_name("body") {
+                            self.collect_local_types(body, types);
+                        }
+                    }
+                    continue;
+                };
+            if let Some(name_node) = child.child_by_field_name("name") {
+                types.push((self.node_text(name_node), shape, self.keeps_item(child)));
+            }
+        }
+    }
+    fn extract_imports(&self, node: Node) -> Vec<Value> {
+        let mut imports = Vec::new();
+        for child in node.children(&mut node.walk()) {
+            if !self.kinds().imports.contains(&child.kind()) {
+                continue;
+            }
+            let declaration = self.node_text(child);
+            // Only Rust's `use` has a structured argument to expand
+            let Some(argument) = child.child_by_field_name("argument") else {
+                imports.push(json!({
+                    "name": declaration,
+                    "declaration": declaration
+                }));
+                continue;
+            };
+            let mut paths = Vec::new();
+            self.collect_use_paths(argument, "", &mut paths);
+            for (path, alias, is_glob) in paths {
+                let name = match (&alias, is_glob) {
+                    (Some(alias), _) => alias.clone(),
+                    (None, true) => "*".to_string(),
+                    (None, false) => path.rsplit("::").next().unwrap_or(&path).to_string(),
+                };
+                imports.push(json!({
+                    "name": name,
+                    "path": path,
+                    "alias": alias,
+                    "is_glob": is_glob,
+                    "declaration": declaration
+                }));
+            }
+        }
+        imports
+    }
+    // Expand a `use` argument into `(path, alias, is_glob)` entries, one per
+    // imported name, flattening grouped imports like `use a::{b, c::*}`
+    fn collect_use_paths(
+        &self,
+        node: Node,
+        prefix: &str,
+        paths: &mut Vec<(String, Option<String>, bool)>,
+    ) {
+        let join = |path: &str| match (prefix.is_empty(), path.is_empty()) {
+            (true, _) => path.to_string(),
+            (false, true) => prefix.to_string(),
+            (false, false) => format!("{}::{}", prefix, path),
+        };
+        match node.kind() {
+            "scoped_use_list" => {
+                let path = node
+                    .child_by_field_name("path")
+                    .map(|n| self.node_text(n))
+                    .unwrap_or_default();
+                if let Some(list) = node.child_by_field_name("list") {
+                    self.collect_use_paths(list, &join(path), paths);
+                }
+            }
+            "use_list" => {
+                for item in node.named_children(&mut node.walk()) {
+                    self.collect_use_paths(item, prefix, paths);
+                }
+            }
+            "use_as_clause" => {
+                let path = node
+                    .child_by_field_name("path")
+                    .map(|n| self.node_text(n))
+                    .unwrap_or_default();
+                let alias = node
+                    .child_by_field_name("alias")
+                    .map(|n| self.node_text(n).to_string());
+                paths.push((join(path), alias, false));
+            }
+            "use_wildcard" => {
+                let path = node
+                    .named_child(0)
+                    .map(|n| self.node_text(n))
+                    .unwrap_or_default();
+                paths.push((join(path), None, true));
+            }
+            // `self` inside a group re-imports the group's own prefix
+            "self" if !prefix.is_empty() => paths.push((prefix.to_string(), None, false)),
+            "line_comment" | "block_comment" => {}
+            _ => paths.push((join(self.node_text(node)), None, false)),
+        }
+    }
+    fn extract_functions(&self, node: Node) -> Vec<FunctionInfo> {
+        let mut functions = Vec::new();
+        for child in node.children(&mut node.walk()) {
+            if self.kinds().functions.contains(&child.kind()) {
+                functions.push(self.function_info(child));
+            }
+        }
+        functions
+    }
+    fn function_info(&self, function_node: Node) -> FunctionInfo {
+        let function_name_node = function_node.child_by_field_name("name").unwrap();
+        let (function_name, raw) = Self::identifier_name(self.node_text(function_name_node));
+        let visibility = self.extract_visibility(function_node);
+        let parameters = self.extract_parameters(function_node);
+        let return_type_node = function_node.child_by_field_name("return_type");
+        let return_type = return_type_node.map(|n| self.node_text(n).to_string());
+        let body = self.body_text(function_node);
+        let called_methods = self.extract_called_methods(function_node);
+        let method_chains = self.extract_method_chains(function_node);
+        let local_variables = self.extract_method_variables(function_node);
+        let (modifiers, extern_abi) = self.extract_function_modifiers(function_node);
+        let nested_functions = self
+            .inner_functions(function_node)
+            .into_iter()
+            .map(|inner| self.function_info(inner))
+            .collect();
+        FunctionInfo {
+            name: function_name.to_string(),
+            path: self.item_path(function_node, function_name),
+            raw,
+            signature: self.function_signature(function_node),
+            visibility: visibility.into_owned(),
+            docs: self.extract_docs(function_node),
+            is_async: modifiers.contains(&"async"),
+            is_const: modifiers.contains(&"const"),
+            is_unsafe: modifiers.contains(&"unsafe"),
+            extern_abi,
+            is_test: self.is_test_function(function_node),
+            parameters,
+            return_type,
+            return_dispatch_types: self.extract_dispatch_types(return_type_node),
+            where_predicates: self.extract_where(function_node),
+            lifetimes: self
+                .extract_lifetimes(function_node)
+                .into_iter()
+                .map(String::from)
+                .collect(),
+            control_flow: self.extract_control_flow(function_node),
+            exits: self.extract_exits(function_node),
+            body: Some(body),
+            called_methods,
+            method_chains,
+            local_variables,
+            nested_functions,
+            position: Position::of(function_node),
+        }
+    }
+    // Functions declared directly inside a function's body (in any block
+    // or expression), but not those nested inside them in turn
+    fn inner_functions<'t>(&self, function_node: Node<'t>) -> Vec<Node<'t>> {
+        let mut inner = Vec::new();
+        if let Some(body) = function_node.child_by_field_name("body") {
+            self.collect_inner_functions(body, &mut inner);
+        }
+        inner
+    }
+    fn collect_inner_functions<'t>(&self, node: Node<'t>, inner: &mut Vec<Node<'t>>) {
+        for child in node.children(&mut node.walk()) {
+            if self.kinds().functions.contains(&child.kind()) {
+                inner.push(child);
+            } else {
+                self.collect_inner_functions(child, inner);
+            }
+        }
+    }
+    // Keyword qualifiers (`async`, `const`, `unsafe`, `default`) and the ABI of
+    // an `extern` qualifier, which is "C" when no ABI string is written
+    fn extract_function_modifiers(
+        &self,
+        function_node: Node,
+    ) -> (Vec<&'static str>, Option<String>) {
+        let mut modifiers = Vec::new();
+        let mut extern_abi = None;
+        let modifiers_node = function_node
+            .children(&mut function_node.walk())
+            .find(|child| child.kind() == "function_modifiers");
+        if let Some(modifiers_node) = modifiers_node {
+            for modifier in modifiers_node.children(&mut modifiers_node.walk()) {
+                match modifier.kind() {
+                    "async" => modifiers.push("async"),
+                    "const" => modifiers.push("const"),
+                    "unsafe" => modifiers.push("unsafe"),
+                    "default" => modifiers.push("default"),
+                    "extern_modifier" => {
+                        let abi = modifier
+                            .named_child(0)
+                            .map(|literal| self.node_text(literal).trim_matches('"').to_string());
+                        extern_abi = Some(abi.unwrap_or_else(|| "C".to_string()));
+                    }
+                    _ => {}
+                }
+            }
+        }
+        (modifiers, extern_abi)
+    }
+    // The ABI of a function pointer type written `extern "ABI" fn(..)` (or
+    // `extern fn`, which is "C"); None for other types, and for plain
+    // `fn(..)` pointers, which are Rust
+    fn fn_pointer_abi(&self, type_node: Node) -> Option<String> {
+        if type_node.kind() != "function_type" {
+            return None;
+        }
+        self.extract_function_modifiers(type_node).1
+    }
+    // `name` behind the names of the modules enclosing `item_node`, under
+    // `module_paths`; None for an item inside a function, which no path
+    // reaches
+    fn item_path(&self, item_node: Node, name: &str) -> Option<String> {
+        if !self.module_paths {
+            return None;
+        }
+        let mut segments = vec![name];
+        let mut ancestor = item_node.parent();
+        while let Some(node) = ancestor {
+            let kinds = self.kinds();
+            if kinds.functions.contains(&node.kind()) {
+                return None;
+            }
+            if kinds.modules.contains(&node.kind()) {
+                if let Some(module_name) = node.child_by_field_name("name") {
+                    segments.push(Self::identifier_name(self.node_text(module_name)).0);
+                }
+            }
+            ancestor = node.parent();
+        }
+        segments.reverse();
+        Some(segments.join("::"))
+    }
+    // Adds the 0-based `start_line`/`start_col`/`end_line`/`end_col` of `node`,
+    // plus its `start_byte`/`end_byte` for slicing the original source
+    fn with_position(mut item: Value, node: Node) -> Value {
+        if let (Value::Object(object), Value::Object(position)) =
+            (&mut item, json!(Position::of(node)))
+        {
+            object.extend(position);
+        }
+        item
+    }
+    // One-line `pub const unsafe fn name<T: Bound>(a: A, b: B) -> R` built
+    // from the extracted pieces. `where` bounds on a type parameter are moved
+    // onto the parameter, so equivalent spellings give the same signature.
+    fn function_signature(&self, function_node: Node) -> String {
+        // Other languages' signatures are given as written, up to the body
+        if self.language != Language::Rust {
+            let written = self.signature_text(function_node);
+            return Self::collapse_whitespace(written.trim_end_matches(':'));
+        }
+        let mut signature = String::new();
+        let visibility = self.extract_visibility(function_node);
+        if visibility != "private" {
+            signature.push_str(&visibility);
+            signature.push(' ');
+        }
+        let (modifiers, extern_abi) = self.extract_function_modifiers(function_node);
+        for modifier in modifiers {
+            signature.push_str(modifier);
+            signature.push(' ');
+        }
+        if let Some(abi) = extern_abi {
+            signature.push_str(&format!("extern \"{}\" ", abi));
+        }
+        let name = function_node
+            .child_by_field_name("name")
+            .map_or("", |n| self.node_text(n));
+        signature.push_str("fn ");
+        signature.push_str(name);
+
+        let generics = self.extract_generics(function_node);
+        if !generics.is_empty() {
+            let rendered: Vec<String> = generics.iter().map(Self::generic_signature).collect();
+            signature.push_str(&format!("<{}>", rendered.join(", ")));
+        }
+
+        let receiver = self.extract_receiver(function_node);
+        let mut parameters: Vec<String> = receiver.iter().cloned().collect();
+        for parameter in self.extract_parameters(function_node) {
+            if receiver.is_some() && parameter.name == "self" {
+                continue;
+            }
+            parameters.push(match parameter.ty {
+                Some(parameter_type) => format!("{}: {}", parameter.name, parameter_type),
+                None => parameter.name,
+            });
+        }
+        signature.push_str(&format!("({})", parameters.join(", ")));
+
+        if let Some(return_type) = function_node.child_by_field_name("return_type") {
+            signature.push_str(" -> ");
+            signature.push_str(self.node_text(return_type));
+        }
+        let predicates: Vec<String> = self
+            .where_predicates(function_node)
+            .into_iter()
+            .filter(|(constrained, _)| !generics.iter().any(|g| g.name == *constrained))
+            .map(|(constrained, bounds)| format!("{}: {}", constrained, bounds.join(" + ")))
+            .collect();
+        if !predicates.is_empty() {
+            signature.push_str(" where ");
+            signature.push_str(&predicates.join(", "));
+        }
+        Self::collapse_whitespace(&signature)
+    }
+    // Lifetimes the signature declares or mentions, in order of first
+    // appearance: `fn f<'a>(x: &'a str) -> Cow<'static, str>` gives `'a`
+    // and `'static`
+    fn extract_lifetimes(&self, function_node: Node) -> Vec<&str> {
+        let mut lifetimes = Vec::new();
+        for field in ["type_parameters", "parameters", "return_type"] {
+            if let Some(part) = function_node.child_by_field_name(field) {
+                self.collect_lifetimes(part, &mut lifetimes);
+            }
+        }
+        if let Some(where_clause) = function_node
+            .children(&mut function_node.walk())
+            .find(|child| child.kind() == "where_clause")
+        {
+            self.collect_lifetimes(where_clause, &mut lifetimes);
+        }
+        lifetimes
+    }
+    fn collect_lifetimes<'s>(&'s self, node: Node, lifetimes: &mut Vec<&'s str>) {
+        if node.kind() == "lifetime" {
+            let lifetime = self.node_text(node);
+            if !lifetimes.contains(&lifetime) {
+                lifetimes.push(lifetime);
+            }
+            return;
+        }
+        for child in node.children(&mut node.walk()) {
+            self.collect_lifetimes(child, lifetimes);
+        }
+    }
+    // `'a: 'b`, `T: Clone + Send = String` or `const N: usize = 4`, from an
+    // `extract_generics` entry
+    fn generic_signature(generic: &GenericParam) -> String {
+        let mut rendered = match &generic.ty {
+            Some(const_type) => format!("const {}: {}", generic.name, const_type),
+            None => generic.name.clone(),
+        };
+        if !generic.bounds.is_empty() {
+            rendered.push_str(": ");
+            rendered.push_str(&generic.bounds.join(" + "));
+        }
+        if let Some(default) = &generic.default {
+            rendered.push_str(" = ");
+            rendered.push_str(default);
+        }
+        rendered
+    }
+    fn collapse_whitespace(text: &str) -> String {
+        text.split_whitespace().collect::<Vec<_>>().join(" ")
+    }
+    // `pub`, `pub(crate)`, `pub(super)`, ... as written, or "private" when absent
+    fn extract_visibility(&self, item_node: Node) -> Cow<'_, str> {
+        item_node
+            .children(&mut item_node.walk())
+            .find(|child| child.kind() == "visibility_modifier")
+            .map_or(Cow::Borrowed("private"), |n| self.visibility_text(n))
+    }
+    // `pub`, `pub(crate)`, `pub(super)`, `pub(self)` or `pub(in a::b)` with
+    // the scope kept exactly, only stray whitespace (`pub( crate )`) removed
+    fn visibility_text(&self, modifier: Node) -> Cow<'_, str> {
+        let text = self.node_text(modifier);
+        let Some(scope) = modifier.named_child(0) else {
+            return Cow::Borrowed(text);
+        };
+        let scope_text: String = self
+            .node_text(scope)
+            .chars()
+            .filter(|c| !c.is_whitespace())
+            .collect();
+        let visibility = match scope.kind() {
+            "crate" | "self" | "super" => format!("pub({})", scope_text),
+            _ => format!("pub(in {})", scope_text),
+        };
+        if visibility == text {
+            Cow::Borrowed(text)
+        } else {
+            Cow::Owned(visibility)
+        }
+    }
+    // Outer doc comments (`///`, `/** */`) directly above an item, skipping
+    // any attributes between them and the item
+    fn extract_docs(&self, item_node: Node) -> Option<String> {
+        let mut lines = Vec::new();
+        let mut sibling = item_node.prev_sibling();
+        while let Some(node) = sibling {
+            match node.kind() {
+                "attribute_item" => {}
+                "line_comment" | "block_comment" if node.child_by_field_name("outer").is_some() => {
+                    lines.push(self.doc_text(node));
+                }
+                _ => break,
+            }
+            sibling = node.prev_sibling();
+        }
+        lines.reverse();
+        Self::join_docs(lines)
+    }
+    // Inner doc comments (`//!`, `/*! */`) written directly inside `node`
+    fn extract_inner_docs(&self, node: Node) -> Option<String> {
+        let lines = node
+            .children(&mut node.walk())
+            .filter(|child| child.child_by_field_name("inner").is_some())
+            .map(|child| self.doc_text(child))
+            .collect();
+        Self::join_docs(lines)
+    }
+    // A module's outer docs followed by the inner docs at the top of its body
+    fn extract_module_docs(&self, mod_node: Node) -> Option<String> {
+        let outer = self.extract_docs(mod_node);
+        let inner = mod_node
+            .child_by_field_name("body")
+            .and_then(|body| self.extract_inner_docs(body));
+        Self::join_docs(outer.into_iter().chain(inner).collect())
+    }
+    fn doc_text(&self, comment_node: Node) -> String {
+        comment_node
+            .child_by_field_name("doc")
+            .map(|doc| {
+                let text = self.node_text(doc);
+                text.strip_prefix(' ')
+                    .unwrap_or(text)
+                    .trim_end()
+                    .to_string()
+            })
+            .unwrap_or_default()
+    }
+    fn join_docs(lines: Vec<String>) -> Option<String> {
+        if lines.is_empty() {
+            None
+        } else {
+            Some(lines.join("\n"))
+        }
+    }
+    fn extract_parameters(&self, function_node: Node) -> Vec<ParameterInfo> {
+        let mut parameters = Vec::new();
+        if let Some(parameters_node) = function_node.child_by_field_name("parameters") {
+            for param in parameters_node.named_children(&mut parameters_node.walk()) {
+                let kinds = self.kinds();
+                if kinds.parameter_separators.contains(&param.kind())
+                    || kinds.comments.contains(&param.kind())
+                {
+                    continue;
+                }
+                let (name_node, default_node) = self.parameter_parts(param);
+                // `self_parameter` (`&mut self`) has no pattern and is
+                // reported as `self`
+                let (param_name, raw) = match param.kind() {
+                    "self_parameter" => ("self", false),
+                    _ => Self::identifier_name(self.node_text(name_node)),
+                };
+                let type_node = param.child_by_field_name("type");
+                let param_type = type_node.map(|n| self.node_text(n).to_string());
+                let is_mutable = param
+                    .children(&mut param.walk())
+                    .any(|n| n.kind() == "mutable_specifier");
+                let is_reference = match param.kind() {
+                    "self_parameter" => self.node_text(param).starts_with('&'),
+                    _ => type_node.is_some_and(|n| n.kind() == "reference_type"),
+                };
+                let default_value = default_node.map(|n| self.node_text(n).to_string());
+                parameters.push(ParameterInfo {
+                    name: param_name.to_string(),
+                    raw,
+                    ty: param_type,
+                    is_mutable,
+                    is_reference,
+                    abi: type_node.and_then(|n| self.fn_pointer_abi(n)),
+                    dispatch_types: self.extract_dispatch_types(type_node),
+                    default_value,
+                });
+            }
+        }
+        parameters
+    }
+    // The nodes holding a parameter's name and default value, by the
+    // language's `parameter_fields`
+    fn parameter_parts<'t>(&self, param: Node<'t>) -> (Node<'t>, Option<Node<'t>>) {
+        let fields = self.kinds().parameter_fields;
+        let Some((_, name_field, default_field)) =
+            fields.iter().find(|(kind, _, _)| *kind == param.kind())
+        else {
+            return (param, None);
+        };
+        let default_node = default_field.and_then(|field| param.child_by_field_name(field));
+        let name_node = match name_field {
+            Some(field) => param.child_by_field_name(field),
+            None => param.named_child(0),
+        };
+        // The name may be wrapped in turn, as in `*args: int`
+        let name_node = name_node.map_or(param, |name| self.parameter_parts(name).0);
+        (name_node, default_node)
+    }
+    // Every `dyn Trait` and `impl Trait` anywhere in a type, outermost
+    // first (`impl Iterator<Item = Box<dyn Error>>` has both), with the
+    // trait it names
+    fn extract_dispatch_types(&self, type_node: Option<Node>) -> Vec<DispatchType> {
+        let mut dispatch_types = Vec::new();
+        if let Some(type_node) = type_node {
+            self.collect_dispatch_types(type_node, &mut dispatch_types);
+        }
+        dispatch_types
+    }
+    fn collect_dispatch_types(&self, node: Node, dispatch_types: &mut Vec<DispatchType>) {
+        let dispatch = match node.kind() {
+            "dynamic_type" => Some(Dispatch::Dynamic),
+            "abstract_type" => Some(Dispatch::ImplTrait),
+            _ => None,
+        };
+        if let (Some(dispatch), Some(trait_node)) = (dispatch, node.child_by_field_name("trait")) {
+            // The trait without its arguments: `Iterator` for
+            // `Iterator<Item = u8>`, `Fn` for `Fn(u8) -> u8`
+            let name_node = match trait_node.kind() {
+                "generic_type" => trait_node.child_by_field_name("type"),
+                "function_type" => trait_node.child_by_field_name("trait"),
+                _ => None,
+            };
+            dispatch_types.push(DispatchType {
+                trait_name: self.node_text(name_node.unwrap_or(trait_node)).to_string(),
+                dispatch,
+                ty: self.node_text(node).to_string(),
+            });
+        }
+        for child in node.named_children(&mut node.walk()) {
+            self.collect_dispatch_types(child, dispatch_types);
+        }
+    }
+    fn extract_called_methods(&self, function_node: Node) -> Vec<CallInfo> {
+        let mut called_methods = Vec::new();
+        if let Some(body_node) = function_node.child_by_field_name("body") {
+            self.collect_calls(body_node, &mut called_methods);
+        }
+        called_methods
+    }
+    // Every call in the subtree, stopping at nested function items whose
+    // calls belong to them rather than to the enclosing function
+    fn collect_calls(&self, node: Node, calls: &mut Vec<CallInfo>) {
+        for descendant in node.children(&mut node.walk()) {
+            if self.kinds().functions.contains(&descendant.kind()) {
+                continue;
+            }
+            if descendant.kind() == "await_expression" {
+                if let Some(awaited) = descendant.named_child(0) {
+                    calls.push(CallInfo {
+                        name: self.node_text(awaited).to_string(),
+                        kind: CallKind::Await,
+                        receiver: Some(self.node_text(awaited).to_string()),
+                        path: Vec::new(),
+                        method: "await".to_string(),
+                    });
+                }
+            }
+            if self.kinds().calls.contains(&descendant.kind()) {
+                if let Some(method_name_node) = descendant.child_by_field_name("function") {
+                    let (receiver, path, method) = self.call_parts(method_name_node);
+                    calls.push(CallInfo {
+                        name: self.node_text(method_name_node).to_string(),
+                        kind: match receiver {
+                            Some(_) => CallKind::Method,
+                            None => CallKind::Function,
+                        },
+                        receiver: receiver.map(String::from),
+                        path: path.into_iter().map(String::from).collect(),
+                        method: method.to_string(),
+                    });
+                }
+            }
+            self.collect_calls(descendant, calls);
+        }
+    }
+    fn extract_method_chains(&self, function_node: Node) -> Vec<MethodChain> {
+        let mut chains = Vec::new();
+        if let Some(body_node) = function_node.child_by_field_name("body") {
+            self.collect_chains(body_node, &mut chains);
+        }
+        chains
+    }
+    // Chains outermost call first, skipping nested function items like
+    // `collect_calls`. A chain's own calls aren't chains again, but their
+    // arguments and the chain's receiver can hold others.
+    fn collect_chains(&self, node: Node, chains: &mut Vec<MethodChain>) {
+        if self.kinds().functions.contains(&node.kind()) {
+            return;
+        }
+        let mut receiver = node;
+        let mut links = Vec::new();
+        while let Some((call, inner, method)) = self.chain_link(receiver) {
+            links.push((call, method));
+            receiver = inner;
+        }
+        if links.is_empty() {
+            for child in node.children(&mut node.walk()) {
+                self.collect_chains(child, chains);
+            }
+            return;
+        }
+        if links.len() > 1 {
+            chains.push(MethodChain {
+                receiver: self.node_text(receiver).to_string(),
+                chain: links
+                    .iter()
+                    .rev()
+                    .map(|(_, method)| method.to_string())
+                    .collect(),
+                position: Position::of(node),
+            });
+        }
+        self.collect_chains(receiver, chains);
+        for (call, _) in links {
+            if let Some(arguments) = call.child_by_field_name("arguments") {
+                self.collect_chains(arguments, chains);
+            }
+        }
+    }
+    // A method call `receiver.method(..)`, seen through a `?` or `.await`
+    // on it, as the call, its receiver and the method's name
+    fn chain_link<'t>(&self, node: Node<'t>) -> Option<(Node<'t>, Node<'t>, &str)> {
+        let mut call = node;
+        while matches!(call.kind(), "try_expression" | "await_expression") {
+            call = call.named_child(0)?;
+        }
+        if !self.kinds().calls.contains(&call.kind()) {
+            return None;
+        }
+        let callee = call.child_by_field_name("function")?;
+        let callee = match callee.kind() {
+            "generic_function" => callee.child_by_field_name("function")?,
+            _ => callee,
+        };
+        if !self.kinds().member_accesses.contains(&callee.kind()) {
+            return None;
+        }
+        let receiver = callee.named_child(0)?;
+        let method = callee.named_child(callee.named_child_count().saturating_sub(1))?;
+        Some((
+            call,
+            receiver,
+            Self::identifier_name(self.node_text(method)).0,
+        ))
+    }
+    // How many of each branching/looping construct the body holds, and how
+    // deeply they nest; an `else if` continues its chain rather than
+    // nesting inside it
+    fn extract_control_flow(&self, function_node: Node) -> ControlFlow {
+        let mut control_flow = ControlFlow::default();
+        for (_, key) in self.kinds().control_flow {
+            control_flow.counts.insert(key.to_string(), 0);
+        }
+        if let Some(body_node) = function_node.child_by_field_name("body") {
+            self.count_control_flow(body_node, 0, &mut control_flow);
+        }
+        control_flow
+    }
+    fn count_control_flow(&self, node: Node, depth: usize, control_flow: &mut ControlFlow) {
+        for child in node.children(&mut node.walk()) {
+            if self.kinds().functions.contains(&child.kind()) {
+                continue;
+            }
+            let construct = self
+                .kinds()
+                .control_flow
+                .iter()
+                .find(|(kind, _)| *kind == child.kind());
+            let mut child_depth = depth;
+            if let Some((kind, key)) = construct {
+                *control_flow.counts.entry(key.to_string()).or_default() += 1;
+                let chained = *kind == "elif_clause" || node.kind() == "else_clause";
+                if !chained {
+                    child_depth += 1;
+                }
+                control_flow.max_depth = control_flow.max_depth.max(child_depth);
+            }
+            self.count_control_flow(child, child_depth, control_flow);
+        }
+    }
+    fn extract_exits(&self, function_node: Node) -> Exits {
+        let mut exits = Exits::default();
+        if let Some(body_node) = function_node.child_by_field_name("body") {
+            self.collect_exits(body_node, &mut exits);
+        }
+        exits
+    }
+    fn collect_exits(&self, node: Node, exits: &mut Exits) {
+        let kinds = self.kinds();
+        for child in node.children(&mut node.walk()) {
+            if kinds.functions.contains(&child.kind()) || kinds.closures.contains(&child.kind()) {
+                continue;
+            }
+            if let Some((_, kind)) = kinds.exits.iter().find(|(exit, _)| *exit == child.kind()) {
+                let count = match kind {
+                    ExitKind::Return => &mut exits.returns,
+                    ExitKind::Break => &mut exits.breaks,
+                    ExitKind::Continue => &mut exits.continues,
+                    ExitKind::Try => &mut exits.tries,
+                };
+                *count += 1;
+                exits.points.push(ExitPoint {
+                    kind: *kind,
+                    position: Position::of(child),
+                });
+            }
+            self.collect_exits(child, exits);
+        }
+    }
+    // A callee split into its receiver, path and the function it names:
+    // `self.items.len` is (`self.items`, [], `len`), `crate::io::read` is
+    // (none, [`crate`, `io`], `read`). Turbofish arguments are dropped, and a
+    // callee that is some other expression (`(handlers[0])()`) is its own name.
+    fn call_parts(&self, callee: Node) -> (Option<&str>, Vec<&str>, &str) {
+        let callee = match callee.kind() {
+            "generic_function" => callee.child_by_field_name("function").unwrap_or(callee),
+            _ => callee,
+        };
+        // `recv.method()` is a call whose callee is a field expression
+        if self.kinds().member_accesses.contains(&callee.kind()) {
+            let count = callee.named_child_count();
+            if let (Some(receiver), Some(method)) = (
+                callee.named_child(0),
+                callee.named_child(count.saturating_sub(1)),
+            ) {
+                let (method, _) = Self::identifier_name(self.node_text(method));
+                return (Some(self.node_text(receiver)), Vec::new(), method);
+            }
+        }
+        if callee.kind() == "scoped_identifier" {
+            if let Some(name) = callee.child_by_field_name("name") {
+                let mut path = Vec::new();
+                if let Some(path_node) = callee.child_by_field_name("path") {
+                    self.path_segments(path_node, &mut path);
+                }
+                return (None, path, Self::identifier_name(self.node_text(name)).0);
+            }
+        }
+        (
+            None,
+            Vec::new(),
+            Self::identifier_name(self.node_text(callee)).0,
+        )
+    }
+    fn path_segments<'s>(&'s self, node: Node, segments: &mut Vec<&'s str>) {
+        match node.kind() {
+            "scoped_identifier" | "scoped_type_identifier" => {
+                if let Some(path) = node.child_by_field_name("path") {
+                    self.path_segments(path, segments);
+                }
+                if let Some(name) = node.child_by_field_name("name") {
+                    segments.push(self.node_text(name));
+                }
+            }
+            // `Vec::<u8>::new` has the path `Vec`
+            "generic_type" => match node.child_by_field_name("type") {
+                Some(base) => self.path_segments(base, segments),
+                None => segments.push(self.node_text(node)),
+            },
+            _ => segments.push(self.node_text(node)),
+        }
+    }
+    // `let` bindings anywhere in the body (nested fns excluded), one entry per
+    // bound name: `let (a, b): (i32, u8) = f();` gives both `a` and `b`, each
+    // with the annotation as `type` and the initializer as `value`
+    fn extract_method_variables(&self, function_node: Node) -> Vec<VariableInfo> {
+        let mut variables = Vec::new();
+        if let Some(body) = function_node.child_by_field_name("body") {
+            self.collect_variables(body, &mut variables);
+        }
+        variables
+    }
+    fn collect_variables(&self, node: Node, variables: &mut Vec<VariableInfo>) {
+        for child in node.named_children(&mut node.walk()) {
+            if self.kinds().functions.contains(&child.kind()) {
+                continue;
+            }
+            // Each binding site as (kind, pattern, type, value): for
+            // `if let`/`while let` and match arms the value is the
+            // expression being matched
+            let binding = match child.kind() {
+                "let_declaration" => Some((
+                    BindingKind::Let,
+                    child.child_by_field_name("pattern"),
+                    child.child_by_field_name("type"),
+                    child.child_by_field_name("value"),
+                )),
+                "let_condition" => Some((
+                    Self::let_condition_kind(child),
+                    child.child_by_field_name("pattern"),
+                    None,
+                    child.child_by_field_name("value"),
+                )),
+                "match_arm" => Some((
+                    BindingKind::Match,
+                    child.child_by_field_name("pattern"),
+                    None,
+                    child
+                        .parent()
+                        .and_then(|block| block.parent())
+                        .and_then(|expression| expression.child_by_field_name("value")),
+                )),
+                _ => None,
+            };
+            if let Some((kind, Some(pattern), type_node, value_node)) = binding {
+                let mut names = Vec::new();
+                self.pattern_bindings(pattern, &mut names);
+                let variable_type = type_node.map(|n| self.node_text(n).to_string());
+                let value = value_node.map(|n| self.node_text(n).to_string());
+                for name in names {
+                    variables.push(VariableInfo {
+                        name,
+                        kind,
+                        pattern: self.node_text(pattern).to_string(),
+                        ty: variable_type.clone(),
+                        value: value.clone(),
+                    });
+                }
+            }
+            self.collect_variables(child, variables);
+        }
+    }
+    // `if let` or `while let`, for a `let_condition` on its own or in a
+    // `let_chain`
+    fn let_condition_kind(condition: Node) -> BindingKind {
+        let mut ancestor = condition.parent();
+        while let Some(node) = ancestor {
+            if node.kind() == "while_expression" {
+                return BindingKind::WhileLet;
+            }
+            if node.kind() == "if_expression" {
+                break;
+            }
+            ancestor = node.parent();
+        }
+        BindingKind::IfLet
+    }
+    // Names a pattern binds, skipping the paths it matches against (`Some`
+    // in `Some(x)`, `Point` in `Point { x, .. }`), a bare capitalized name,
+    // which is a unit variant or constant (`None`, `MAX`), and a match arm's
+    // guard. Each name is listed once, though the branches of `A(x) | B(x)`
+    // all bind it.
+    fn pattern_bindings(&self, pattern: Node, names: &mut Vec<String>) {
+        match pattern.kind() {
+            "identifier" | "shorthand_field_identifier" => {
+                let name = self.node_text(pattern);
+                let is_path =
+                    pattern.kind() == "identifier" && name.starts_with(|c: char| c.is_uppercase());
+                if !is_path && !names.iter().any(|existing| existing == name) {
+                    names.push(name.to_string());
+                }
+            }
+            "match_pattern" => {
+                let guard = pattern.child_by_field_name("condition");
+                for child in pattern.named_children(&mut pattern.walk()) {
+                    if Some(child) != guard {
+                        self.pattern_bindings(child, names);
+                    }
+                }
+            }
+            "field_pattern" => match pattern.child_by_field_name("pattern") {
+                Some(inner) => self.pattern_bindings(inner, names),
+                None => {
+                    if let Some(name) = pattern.child_by_field_name("name") {
+                        self.pattern_bindings(name, names);
+                    }
+                }
+            },
+            _ => {
+                let path = pattern.child_by_field_name("type");
+                for child in pattern.named_children(&mut pattern.walk()) {
+                    if Some(child) != path {
+                        self.pattern_bindings(child, names);
+                    }
+                }
+            }
+        }
+    }
+    fn extract_structs(&self, node: Node) -> Vec<StructInfo> {
+        let mut structs = Vec::new();
+        for child in node.children(&mut node.walk()) {
+            if self.kinds().structs.contains(&child.kind()) {
+                if let Some(struct_name_node) = child.child_by_field_name("name") {
+                    let (struct_name, raw) =
+                        Self::identifier_name(self.node_text(struct_name_node));
+                    let visibility = self.extract_visibility(child);
+                    let fields = self.extract_fields(child);
+                    structs.push(StructInfo {
+                        name: struct_name.to_string(),
+                        path: self.item_path(child, struct_name),
+                        raw,
+                        generics: self.extract_generics(child),
+                        where_predicates: self.extract_where(child),
+                        visibility: visibility.into_owned(),
+                        docs: self.extract_docs(child),
+                        struct_kind: self.struct_kind(child),
+                        repr: self.repr_hints(child),
+                        fields,
+                        position: Position::of(child),
+                    });
+                }
+            }
+        }
+        structs
+    }
+    // Classes in other languages are none of Rust's three kinds
+    fn struct_kind(&self, struct_node: Node) -> Option<StructKind> {
+        if self.language != Language::Rust {
+            return None;
+        }
+        let kind = match struct_node
+            .child_by_field_name("body")
+            .map(|body| body.kind())
+        {
+            None => StructKind::Unit,
+            Some("ordered_field_declaration_list") => StructKind::Tuple,
+            Some(_) => StructKind::Named,
+        };
+        Some(kind)
+    }
+    fn extract_fields(&self, struct_node: Node) -> Vec<FieldInfo> {
+        let mut fields = Vec::new();
+        if let Some(body_node) = struct_node.child_by_field_name("body") {
+            if body_node.kind() == "ordered_field_declaration_list" {
+                return self.extract_tuple_fields(body_node);
+            }
+            for field in body_node.named_children(&mut body_node.walk()) {
+                if field.kind() != "field_declaration" {
+                    continue;
+                }
+                let Some(field_name_node) = field.child_by_field_name("name") else {
+                    continue;
+                };
+                let (field_name, raw) = Self::identifier_name(self.node_text(field_name_node));
+                let type_node = field.child_by_field_name("type");
+                fields.push(FieldInfo {
+                    name: field_name.to_string(),
+                    raw,
+                    index: fields.len(),
+                    ty: type_node.map(|n| self.node_text(n).to_string()),
+                    abi: type_node.and_then(|n| self.fn_pointer_abi(n)),
+                    visibility: self.extract_visibility(field).into_owned(),
+                    attributes: self.extract_metadata(field),
+                });
+            }
+        }
+        fields
+    }
+    // Tuple-struct fields have no names, so they are reported by position
+    fn extract_tuple_fields(&self, body_node: Node) -> Vec<FieldInfo> {
+        let mut fields = Vec::new();
+        let mut cursor = body_node.walk();
+        for (index, type_node) in body_node
+            .children_by_field_name("type", &mut cursor)
+            .enumerate()
+        {
+            let visibility = type_node
+                .prev_named_sibling()
+                .filter(|n| n.kind() == "visibility_modifier")
+                .map_or(Cow::Borrowed("private"), |n| self.visibility_text(n));
+            fields.push(FieldInfo {
+                name: index.to_string(),
+                raw: false,
+                index,
+                ty: Some(self.node_text(type_node).to_string()),
+                abi: self.fn_pointer_abi(type_node),
+                visibility: visibility.into_owned(),
+                attributes: Vec::new(),
+            });
+        }
+        fields
+    }
+    // Generic parameters with their inline bounds, merged with the bounds
+    // that the item's `where` clause places on the same parameter
+    fn extract_generics(&self, item_node: Node) -> Vec<GenericParam> {
+        let mut generics = Vec::new();
+        let Some(params_node) = item_node.child_by_field_name("type_parameters") else {
+            return generics;
+        };
+        for param in params_node.named_children(&mut params_node.walk()) {
+            let (name_node, bounds_node, default_node) = match param.kind() {
+                // tree-sitter-rust 0.23.3 wraps every parameter, bounds and
+                // default included, in one of these
+                "type_parameter" | "lifetime_parameter" => (
+                    param.child_by_field_name("name"),
+                    param.child_by_field_name("bounds"),
+                    param.child_by_field_name("default_type"),
+                ),
+                "const_parameter" => (
+                    param.child_by_field_name("name"),
+                    None,
+                    param.child_by_field_name("value"),
+                ),
+                "optional_type_parameter" => (
+                    param.child_by_field_name("name"),
+                    None,
+                    param.child_by_field_name("default_type"),
+                ),
+                "constrained_type_parameter" | "type_identifier" | "lifetime" => {
+                    (Some(param), None, None)
+                }
+                _ => continue,
+            };
+            let Some(name_node) = name_node else {
+                continue;
+            };
+            // `T: Clone` may itself be the name of an optional parameter
+            let (name_node, bounds_node) = match name_node.kind() {
+                "constrained_type_parameter" => (
+                    name_node.child_by_field_name("left").unwrap_or(name_node),
+                    name_node.child_by_field_name("bounds"),
+                ),
+                _ => (name_node, bounds_node),
+            };
+            let bounds = bounds_node
+                .map(|bounds| self.extract_bounds(bounds))
+                .unwrap_or_default();
+            let param_kind = match (param.kind(), name_node.kind()) {
+                ("const_parameter", _) => GenericKind::Const,
+                (_, "lifetime") => GenericKind::Lifetime,
+                _ => GenericKind::Type,
+            };
+            // The declared type of a const generic (`usize` in `const N: usize`)
+            let const_type = match param.kind() {
+                "const_parameter" => param
+                    .child_by_field_name("type")
+                    .map(|n| self.node_text(n).to_string()),
+                _ => None,
+            };
+            generics.push(GenericParam {
+                name: self.node_text(name_node).to_string(),
+                param_kind,
+                ty: const_type,
+                bounds: bounds.into_iter().map(String::from).collect(),
+                default: default_node.map(|n| self.node_text(n).to_string()),
+            });
+        }
+        for (name, bounds) in self.where_predicates(item_node) {
+            if let Some(generic) = generics.iter_mut().find(|generic| generic.name == name) {
+                generic.bounds.extend(bounds.into_iter().map(String::from));
+            }
+        }
+        generics
+    }
+    // `where` clause constraints as type and bounds pairs
+    fn extract_where(&self, item_node: Node) -> Vec<WherePredicate> {
+        self.where_predicates(item_node)
+            .into_iter()
+            .map(|(constrained, bounds)| WherePredicate {
+                ty: constrained.to_string(),
+                bounds: bounds.into_iter().map(String::from).collect(),
+            })
+            .collect()
+    }
+    fn where_predicates(&self, item_node: Node) -> Vec<(&str, Vec<&str>)> {
+        let mut predicates = Vec::new();
+        let where_clause = item_node
+            .children(&mut item_node.walk())
+            .find(|child| child.kind() == "where_clause");
+        if let Some(where_clause) = where_clause {
+            for predicate in where_clause.named_children(&mut where_clause.walk()) {
+                if let (Some(left), Some(bounds)) = (
+                    predicate.child_by_field_name("left"),
+                    predicate.child_by_field_name("bounds"),
+                ) {
+                    predicates.push((self.node_text(left), self.extract_bounds(bounds)));
+                }
+            }
+        }
+        predicates
+    }
+    fn extract_enums(&self, node: Node) -> Vec<EnumInfo> {
+        let mut enums = Vec::new();
+        for child in node.children(&mut node.walk()) {
+            if self.kinds().enums.contains(&child.kind()) {
+                let enum_name_node = child.child_by_field_name("name").unwrap();
+                let enum_name = self.node_text(enum_name_node);
+                let visibility = self.extract_visibility(child);
+                let variants = self.extract_variants(child);
+                enums.push(EnumInfo {
+                    name: enum_name.to_string(),
+                    path: self.item_path(child, enum_name),
+                    generics: self.extract_generics(child),
+                    where_predicates: self.extract_where(child),
+                    visibility: visibility.into_owned(),
+                    docs: self.extract_docs(child),
+                    repr: self.repr_hints(child),
+                    variants,
+                    position: Position::of(child),
+                });
+            }
+        }
+        enums
+    }
+    fn extract_variants(&self, enum_node: Node) -> Vec<VariantInfo> {
+        let mut variants = Vec::new();
+        if let Some(body_node) = enum_node.child_by_field_name("body") {
+            for variant in body_node.named_children(&mut body_node.walk()) {
+                if variant.kind() != "enum_variant" {
+                    continue;
+                }
+                let variant_name_node = variant.child_by_field_name("name").unwrap();
+                let (variant_name, raw) = Self::identifier_name(self.node_text(variant_name_node));
+                // Tuple and struct variants share the struct body shapes, so
+                // extract_fields reports positional or named fields for both
+                let fields = self.extract_fields(variant);
+                let discriminant = variant
+                    .child_by_field_name("value")
+                    .map(|n| self.node_text(n).to_string());
+                variants.push(VariantInfo {
+                    name: variant_name.to_string(),
+                    raw,
+                    fields,
+                    discriminant,
+                });
+            }
+        }
+        variants
+    }
+    fn extract_traits(&self, node: Node) -> Vec<Value> {
+        let mut traits = Vec::new();
+        for child in node.children(&mut node.walk()) {
+            if self.kinds().traits.contains(&child.kind()) {
+                if let Some(trait_name_node) = child.child_by_field_name("name") {
+                    let trait_name = self.node_text(trait_name_node);
+                    let supertraits = child
+                        .child_by_field_name("bounds")
+                        .map(|bounds| self.extract_bounds(bounds))
+                        .unwrap_or_default();
+                    let items = self.extract_trait_items(child);
+                    traits.push(json!({
+                        "name": trait_name,
+                        "supertraits": supertraits,
+                        "children": items
+                    }));
+                }
+            }
+        }
+        traits
+    }
+    fn extract_trait_items(&self, trait_node: Node) -> Vec<Value> {
+        let mut items = Vec::new();
+        if let Some(body_node) = trait_node.child_by_field_name("body") {
+            for item in body_node.named_children(&mut body_node.walk()) {
+                let Some(name_node) = item.child_by_field_name("name") else {
+                    continue;
+                };
+                let item_name = self.node_text(name_node);
+                match item.kind() {
+                    "function_signature_item" | "function_item" => {
+                        // Only a provided method comes with a body; an
+                        // implementor must supply the rest
+                        items.push(json!({
+                            "type": "method",
+                            "name": item_name,
+                            "signature": self.signature_text(item),
+                            "has_default": item.child_by_field_name("body").is_some()
+                        }));
+                    }
+                    "associated_type" => {
+                        let bounds = item
+                            .child_by_field_name("bounds")
+                            .map(|bounds| self.extract_bounds(bounds))
+                            .unwrap_or_default();
+                        items.push(json!({
+                            "type": "associated_type",
+                            "name": item_name,
+                            "bounds": bounds
+                        }));
+                    }
+                    _ => {}
+                }
+            }
+        }
+        items
+    }
+    fn extract_bounds(&self, bounds_node: Node) -> Vec<&str> {
+        bounds_node
+            .named_children(&mut bounds_node.walk())
+            .map(|bound| self.node_text(bound))
+            .collect()
+    }
+    // Text of a function up to (but excluding) its body, without the trailing `;`
+    pub(crate) fn signature_text(&self, function_node: Node) -> String {
+        let end = function_node
+            .child_by_field_name("body")
+            .map_or(function_node.end_byte(), |body| body.start_byte());
+        self.source_slice(function_node.start_byte()..end)
+            .trim_end()
+            .trim_end_matches(';')
+            .to_string()
+    }
+    fn extract_type_aliases(&self, node: Node) -> Vec<Value> {
+        let mut aliases = Vec::new();
+        for child in node.children(&mut node.walk()) {
+            if self.kinds().type_aliases.contains(&child.kind()) {
+                if let Some(alias_name_node) = child.child_by_field_name("name") {
+                    let aliased_type = child.child_by_field_name("type").map(|n| self.node_text(n));
+                    let generics = child
+                        .child_by_field_name("type_parameters")
+                        .map(|n| self.node_text(n));
+                    let mut alias = json!({
+                        "name": self.node_text(alias_name_node),
+                        "type": aliased_type,
+                        "generics": generics
+                    });
+                    let type_node = child.child_by_field_name("type");
+                    if let Some(abi) = type_node.and_then(|n| self.fn_pointer_abi(n)) {
+                        alias["abi"] = json!(abi);
+                    }
+                    aliases.push(alias);
+                }
+            }
+        }
+        aliases
+    }
+    fn extract_relations(&self, node: Node) -> Vec<Value> {
+        let mut relations = Vec::new();
+        for child in node.children(&mut node.walk()) {
+            if self.kinds().impls.contains(&child.kind()) {
+                // impl_item carries its target under `type`; it has no `name` field
+                if let Some(name_node) = child.child_by_field_name("type") {
+                    let type_name = self.node_text(name_node);
+                    let trait_node = child.child_by_field_name("trait");
+                    let trait_name = trait_node.map(|n| self.node_text(n));
+                    let impl_kind = if trait_node.is_some() {
+                        "trait"
+                    } else {
+                        "inherent"
+                    };
+                    let generics = self.extract_generics(child);
+                    let target_generics = self.extract_type_arguments(name_node, &generics);
+                    relations.push(json!({
+                        "type": "impl",
+                        "for": type_name,
+                        "trait": trait_name,
+                        "impl_kind": impl_kind,
+                        "is_negative": Self::is_negative_impl(child),
+                        "generics": generics,
+                        "target_generics": target_generics,
+                        "where": self.extract_where(child),
+                        "children": self.extract_methods(child),
+                    }));
+                }
+            } else if child.kind() == "attribute_item" {
+                let attribute = self.attribute_info(child);
+                if attribute.path.as_deref() == Some("derive") {
+                    let traits: Vec<&str> = attribute
+                        .args
+                        .iter()
+                        .map(|arg| arg.value.as_str())
+                        .collect();
+                    relations.push(json!({
+                        "type": "derive",
+                        "for": self.attributed_item_name(child),
+                        "traits": traits,
+                        "details": attribute
+                    }));
+                }
+            }
+        }
+        relations
+    }
+    // The arguments applied to a generic type, `<K, String, 'a, 3, Item = u8>`
+    // in `HashMap<K, String, ...>`, each marked `is_param` when it names one
+    // of the impl's own `generics` rather than a concrete type
+    fn extract_type_arguments(&self, type_node: Node, generics: &[GenericParam]) -> Vec<Value> {
+        let Some(arguments) = type_node.child_by_field_name("type_arguments") else {
+            return Vec::new();
+        };
+        let mut extracted = Vec::new();
+        for argument in arguments.named_children(&mut arguments.walk()) {
+            let (kind, name, value) = match argument.kind() {
+                "lifetime" => ("lifetime", None, self.node_text(argument)),
+                "type_binding" => (
+                    "binding",
+                    argument
+                        .child_by_field_name("name")
+                        .map(|n| self.node_text(n)),
+                    argument
+                        .child_by_field_name("type")
+                        .map_or("", |n| self.node_text(n)),
+                ),
+                "block" | "integer_literal" | "boolean_literal" | "char_literal"
+                | "negative_literal" => ("const", None, self.node_text(argument)),
+                "line_comment" | "block_comment" => continue,
+                _ => ("type", None, self.node_text(argument)),
+            };
+            let is_param = kind != "binding" && generics.iter().any(|g| g.name == value);
+            extracted.push(json!({
+                "kind": kind,
+                "name": name,
+                "value": value,
+                "is_param": is_param,
+            }));
+        }
+        extracted
+    }
+    // `impl !Send for T`, which promises the type never implements the trait
+    fn is_negative_impl(impl_node: Node) -> bool {
+        impl_node
+            .children(&mut impl_node.walk())
+            .any(|child| child.kind() == "!")
+    }
+    // Graph edges between items: `trait -> type` for each trait impl (but
+    // not a negative one), `type -> trait` for each derived trait
+    fn extract_edges(&self, node: Node) -> Vec<Value> {
+        let mut edges = Vec::new();
+        for child in node.children(&mut node.walk()) {
+            let kinds = self.kinds();
+            if kinds.impls.contains(&child.kind()) && !Self::is_negative_impl(child) {
+                if let (Some(trait_node), Some(type_node)) = (
+                    child.child_by_field_name("trait"),
+                    child.child_by_field_name("type"),
+                ) {
+                    edges.push(json!({
+                        "from": self.type_name(trait_node),
+                        "to": self.type_name(type_node),
+                        "kind": "implements"
+                    }));
+                }
+            } else if kinds.structs.contains(&child.kind()) || kinds.enums.contains(&child.kind()) {
+                if let Some(name_node) = child.child_by_field_name("name") {
+                    let type_name = self.node_text(name_node);
+                    for derived in self.derived_traits(child) {
+                        edges.push(json!({
+                            "from": type_name,
+                            "to": derived,
+                            "kind": "derives"
+                        }));
+                    }
+                }
+            } else if kinds.modules.contains(&child.kind()) {
+                if let Some(body) = child.child_by_field_name("body") {
+                    edges.extend(self.extract_edges(body));
+                }
+            }
+        }
+        edges
+    }
+    // A type or trait by its bare name, without path or generic arguments:
+    // `A` for `A<'_, T, 3>`, `Display` for `fmt::Display`
+    fn type_name(&self, type_node: Node) -> &str {
+        let mut segments = Vec::new();
+        self.path_segments(type_node, &mut segments);
+        segments.last().copied().unwrap_or_default()
+    }
+    fn extract_methods(&self, impl_node: Node) -> Vec<Value> {
+        let mut methods = Vec::new();
+        if let Some(body_node) = impl_node.child_by_field_name("body") {
+            for item in body_node.named_children(&mut body_node.walk()) {
+                if item.kind() == "function_item" {
+                    if let Some(method_name_node) = item.child_by_field_name("name") {
+                        let return_type_node = item.child_by_field_name("return_type");
+                        let receiver = self.extract_receiver(item);
+                        // Called as `value.name()` with a receiver, else as
+                        // `Type::name()`
+                        let kind = if receiver.is_some() {
+                            "method"
+                        } else {
+                            "associated_fn"
+                        };
+                        methods.push(json!({
+                            "name": self.node_text(method_name_node),
+                            "kind": kind,
+                            "signature": self.function_signature(item),
+                            "visibility": self.extract_visibility(item),
+                            "receiver": receiver,
+                            "parameters": self.extract_parameters(item),
+                            "return_type": return_type_node.map(|n| self.node_text(n)),
+                            "return_dispatch_types": self.extract_dispatch_types(return_type_node),
+                            "where": self.extract_where(item),
+                        }));
+                    }
+                }
+            }
+        }
+        methods
+    }
+    // `self`, `&self`, `&mut self` or `mut self`, or an explicitly typed
+    // `self: Box<Self>`; None for associated functions
+    fn extract_receiver(&self, function_node: Node) -> Option<String> {
+        let parameters_node = function_node.child_by_field_name("parameters")?;
+        let first = parameters_node
+            .named_children(&mut parameters_node.walk())
+            .next()?;
+        let is_mutable = first
+            .children(&mut first.walk())
+            .any(|n| n.kind() == "mutable_specifier");
+        match first.kind() {
+            "self_parameter" => {
+                let is_reference = self.node_text(first).starts_with('&');
+                let receiver = match (is_reference, is_mutable) {
+                    (true, true) => "&mut self",
+                    (true, false) => "&self",
+                    (false, true) => "mut self",
+                    (false, false) => "self",
+                };
+                Some(receiver.to_string())
+            }
+            "parameter" => {
+                let pattern = first.child_by_field_name("pattern")?;
+                if pattern.kind() != "self" {
+                    return None;
+                }
+                let self_type = first.child_by_field_name("type")?;
+                let binding = if is_mutable { "mut self" } else { "self" };
+                Some(format!("{}: {}", binding, self.node_text(self_type)))
+            }
+            _ => None,
+        }
+    }
+    fn extract_constants(&self, node: Node) -> Vec<Value> {
+        let mut constants = Vec::new();
+        for child in node.children(&mut node.walk()) {
+            if self.kinds().constants.contains(&child.kind()) {
+                let constant_name = self.node_text(child.child_by_field_name("name").unwrap());
+                let constant_value =
+                    self.node_text(child.child_by_field_name("value").unwrap_or(child));
+                constants.push(Self::with_position(
+                    json!({
+                        "name": constant_name,
+                        "value": constant_value
+                    }),
+                    child,
+                ));
+            }
+        }
+        constants
+    }
+    // `macro_rules!` definitions and every macro invocation in the subtree
+    fn extract_macros(&self, node: Node) -> Vec<Value> {
+        let mut macros = Vec::new();
+        for child in node.children(&mut node.walk()) {
+            match child.kind() {
+                "macro_definition" => {
+                    if let Some(macro_name_node) = child.child_by_field_name("name") {
+                        macros.push(Self::with_position(
+                            json!({
+                                "type": "definition",
+                                "name": self.node_text(macro_name_node),
+                            }),
+                            child,
+                        ));
+                    }
+                }
+                "macro_invocation" => {
+                    if let Some(macro_path_node) = child.child_by_field_name("macro") {
+                        let arguments = child
+                            .named_children(&mut child.walk())
+                            .find(|n| n.kind() == "token_tree")
+                            .map(|n| self.node_text(n));
+                        macros.push(Self::with_position(
+                            json!({
+                                "type": "invocation",
+                                "name": self.node_text(macro_path_node),
+                                "arguments": arguments,
+                            }),
+                            child,
+                        ));
+                    }
+                }
+                _ => macros.extend(self.extract_macros(child)),
+            }
+        }
+        macros
+    }
+    fn extract_modules_and_impls(&self, node: Node) -> Vec<Value> {
+        let mut modules = Vec::new();
+        for child in node.children(&mut node.walk()) {
+            let kinds = self.kinds();
+            let is_container =
+                kinds.modules.contains(&child.kind()) || kinds.impls.contains(&child.kind());
+            if is_container && self.keeps_item(child) {
+                // An impl is named by its full target type (`Vec<T>`)
+                let name_field = if kinds.impls.contains(&child.kind()) {
+                    "type"
+                } else {
+                    "name"
+                };
+                if let Some(name_node) = child.child_by_field_name(name_field) {
+                    let name = self.node_text(name_node);
+                    let docs = match child.kind() {
+                        "mod_item" => self.extract_module_docs(child),
+                        _ => self.extract_docs(child),
+                    };
+                    let children = match child.kind() {
+                        "mod_item" => child
+                            .child_by_field_name("body")
+                            .map(|body| self.extract_module_items(body))
+                            .unwrap_or_default(),
+                        _ => child
+                            .child_by_field_name("body")
+                            .map(|body| self.extract_nested(body))
+                            .unwrap_or_default(),
+                    };
+                    modules.push(json!({
+                        "type": child.kind(),
+                        "name": name,
+                        "docs": docs,
+                        "is_cfg_test": self.is_cfg_test(child),
+                        "children": children,
+                    }));
+                }
+            }
+        }
+        modules
+    }
+    // Items declared inside a module body, each tagged with its node kind;
+    // nested modules recurse through extract_modules_and_impls
+    fn extract_module_items(&self, body_node: Node) -> Vec<Value> {
+        let mut items = Vec::new();
+        let categories = [
+            ("function_item", json!(self.functions_in(body_node))),
+            ("struct_item", json!(self.visible_structs(body_node))),
+            ("enum_item", json!(self.visible_enums(body_node))),
+        ];
+        for (kind, category_items) in categories {
+            let Value::Array(category_items) = category_items else {
+                continue;
+            };
+            for mut item in category_items {
+                item["type"] = json!(kind);
+                items.push(item);
+            }
+        }
+        items.extend(
+            self.extract_modules_and_impls(body_node)
+                .into_iter()
+                .filter(|item| item["type"] == "mod_item"),
+        );
+        items
+    }
+    // Test functions in `node` and, recursively, in the modules it declares;
+    // `module` is the `::`-joined path of those modules
+    fn extract_tests(&self, node: Node, module: Option<&str>) -> Vec<Value> {
+        let mut tests = Vec::new();
+        for child in node.children(&mut node.walk()) {
+            let kinds = self.kinds();
+            if !self.keeps_item(child) {
+                continue;
+            }
+            if kinds.functions.contains(&child.kind()) && self.is_test_function(child) {
+                if let Some(name_node) = child.child_by_field_name("name") {
+                    tests.push(Self::with_position(
+                        json!({
+                            "name": self.node_text(name_node),
+                            "module": module,
+                        }),
+                        child,
+                    ));
+                }
+            } else if kinds.modules.contains(&child.kind()) {
+                if let (Some(name_node), Some(body)) = (
+                    child.child_by_field_name("name"),
+                    child.child_by_field_name("body"),
+                ) {
+                    let name = self.node_text(name_node);
+                    let path = match module {
+                        Some(parent) => format!("{}::{}", parent, name),
+                        None => name.to_string(),
+                    };
+                    tests.extend(self.extract_tests(body, Some(&path)));
+                }
+            }
+        }
+        tests
+    }
+    fn extract_metadata(&self, node: Node) -> Vec<AttributeInfo> {
+        let mut metadata = Vec::new();
+        for child in node.children(&mut node.walk()) {
+            if child.kind() == "attribute_item" {
+                metadata.push(self.attribute_info(child));
+            }
+        }
+        metadata
+    }
+    // `ERROR` and `MISSING` nodes tree-sitter inserted while recovering from
+    // invalid syntax, with the source line they start on for context
+    fn extract_errors(&self, node: Node) -> Vec<Value> {
+        let mut errors = Vec::new();
+        for child in node.children(&mut node.walk()) {
+            if child.is_error() || child.is_missing() {
+                let start = child.start_position();
+                let context = self.code.lines().nth(start.row).unwrap_or("");
+                errors.push(Self::with_position(
+                    json!({
+                        "type": if child.is_missing() { "missing" } else { "error" },
+                        "kind": child.kind(),
+                        "text": self.node_text(child),
+                        "context": context,
+                    }),
+                    child,
+                ));
+            } else if child.has_error() {
+                errors.extend(self.extract_errors(child));
+            }
+        }
+        errors
+    }
+    // Every literal anywhere under `node`, in source order
+    fn extract_literals(&self, node: Node) -> Vec<Value> {
+        let mut literals = Vec::new();
+        for child in node.children(&mut node.walk()) {
+            let literal_type = self
+                .kinds()
+                .literals
+                .iter()
+                .find(|(kind, _)| *kind == child.kind());
+            match literal_type {
+                Some((_, literal_type)) => {
+                    let value = self.node_text(child);
+                    literals.push(Self::with_position(
+                        json!({
+                            "type": literal_type,
+                            "value": value,
+                            "raw_hashes": Self::raw_string_hashes(value),
+                        }),
+                        child,
+                    ))
+                }
+                None => literals.extend(self.extract_literals(child)),
+            }
+        }
+        literals
+    }
+    fn extract_nested(&self, node: Node) -> Vec<Value> {
+        let mut nested_items = Vec::new();
+        for child in node.children(&mut node.walk()) {
+            // match child.kind() {
+            // "mod_item" | "impl_item" | "function_item" | "struct_item" | "fn" => {
+            nested_items.extend(self.nested_item(child));
+            // }
+            // _ => {}
+            // }
+        }
+        nested_items
+    }
+    fn nested_item(&self, node: Node) -> Option<Value> {
+        let name_node = node.child_by_field_name("name")?;
+        if !self.keeps_item(node) {
+            return None;
+        }
+        let mut children = self.extract_nested(node);
+        // A function's body has no name, so reach into it for the functions
+        // declared there
+        if self.kinds().functions.contains(&node.kind()) {
+            for inner in self.inner_functions(node) {
+                children.extend(self.nested_item(inner));
+            }
+        }
+        Some(json!({
+            "type": node.kind(),
+            "name": self.node_text(name_node),
+            "children": children,
+        }))
+    }
+    fn extract_globals(&self, node: Node) -> Vec<Value> {
+        let mut globals = Vec::new();
+        for child in node.children(&mut node.walk()) {
+            if self.kinds().globals.contains(&child.kind()) {
+                let global_name = self.node_text(child.child_by_field_name("name").unwrap());
+                let global_type = child.child_by_field_name("type").map(|n| self.node_text(n));
+                let global_value =
+                    self.node_text(child.child_by_field_name("value").unwrap_or(child));
+                globals.push(json!({
+                    "name": global_name,
+                    "type": global_type,
+                    "value": global_value,
+                }));
+            }
+        }
+        globals
+    }
+    // Foreign declarations have no body, so they get their own entries
+    // rather than going through `extract_functions`/`extract_globals`
+    fn extract_foreign_items(&self, node: Node) -> Vec<Value> {
+        let mut blocks = Vec::new();
+        for child in node.children(&mut node.walk()) {
+            if !self.kinds().foreign_modules.contains(&child.kind()) {
+                continue;
+            }
+            // `extern { ... }` without an ABI string is `extern "C"`
+            let abi = child
+                .children(&mut child.walk())
+                .find(|modifier| modifier.kind() == "extern_modifier")
+                .and_then(|modifier| modifier.named_child(0))
+                .map_or("C", |literal| self.node_text(literal).trim_matches('"'));
+            let mut items = Vec::new();
+            if let Some(body) = child.child_by_field_name("body") {
+                for item in body.named_children(&mut body.walk()) {
+                    let Some(name_node) = item.child_by_field_name("name") else {
+                        continue;
+                    };
+                    let name = self.node_text(name_node);
+                    let entry = match item.kind() {
+                        "function_signature_item" => json!({
+                            "kind": "function",
+                            "name": name,
+                            "visibility": self.extract_visibility(item),
+                            "docs": self.extract_docs(item),
+                            "parameters": self.extract_parameters(item),
+                            "is_variadic": item
+                                .child_by_field_name("parameters")
+                                .is_some_and(|parameters| {
+                                    parameters
+                                        .named_children(&mut parameters.walk())
+                                        .any(|param| param.kind() == "variadic_parameter")
+                                }),
+                            "return_type": item
+                                .child_by_field_name("return_type")
+                                .map(|n| self.node_text(n)),
+                            "signature": self.declaration_text(item),
+                        }),
+                        "static_item" => json!({
+                            "kind": "static",
+                            "name": name,
+                            "visibility": self.extract_visibility(item),
+                            "docs": self.extract_docs(item),
+                            "type": item.child_by_field_name("type").map(|n| self.node_text(n)),
+                            "is_mutable": item
+                                .children(&mut item.walk())
+                                .any(|n| n.kind() == "mutable_specifier"),
+                            "signature": self.declaration_text(item),
+                        }),
+                        "associated_type" => json!({
+                            "kind": "type",
+                            "name": name,
+                            "visibility": self.extract_visibility(item),
+                            "docs": self.extract_docs(item),
+                            "signature": self.declaration_text(item),
+                        }),
+                        _ => continue,
+                    };
+                    items.push(Self::with_position(entry, item));
+                }
+            }
+            blocks.push(Self::with_position(
+                json!({ "abi": abi, "items": items }),
+                child,
+            ));
+        }
+        blocks
+    }
+    // A declaration on one line, whitespace collapsed and without its `;`
+    fn declaration_text(&self, node: Node) -> String {
+        Self::collapse_whitespace(self.node_text(node).trim_end_matches(';'))
+    }
+    fn extract_schema(&self, node: Node) -> Vec<Value> {
+        let mut schemas = Vec::new();
+        for child in node.children(&mut node.walk()) {
+            if self.kinds().structs.contains(&child.kind()) {
+                let derives = self.derived_traits(child);
+                let is_serde = derives.iter().any(|derive| {
+                    let name = derive.rsplit("::").next().unwrap_or(derive);
+                    name == "Serialize" || name == "Deserialize"
+                });
+                if !is_serde {
+                    continue;
+                }
+                let struct_name = self.node_text(child.child_by_field_name("name").unwrap());
+                let attributes: Vec<AttributeInfo> = self
+                    .item_attributes(child)
+                    .into_iter()
+                    .map(|attribute| self.attribute_info(attribute))
+                    .collect();
+                let fields = self.extract_fields(child);
+                // Extract relationships based on field attributes or annotations
+                let relationships = fields
+                    .iter()
+                    .filter(|field| {
+                        field
+                            .attributes
+                            .iter()
+                            .any(|attribute| attribute.attribute.contains("foreign_key"))
+                    })
+                    .map(|field| {
+                        json!({
+                            "field": field.name,
+                            "relationship": "foreign_key"
+                        })
+                    })
+                    .collect::<Vec<_>>();
+                schemas.push(json!({
+                    "struct": struct_name,
+                    "derives": derives,
+                    "attributes": attributes,
+                    "fields": fields,
+                    "relationships": relationships
+                }));
+            }
+        }
+        schemas
+    }
+    // The name of the item an `attribute_item` is written above, past any
+    // other attributes and comments in between
+    fn attributed_item_name(&self, attribute_item: Node) -> Option<&str> {
+        let mut sibling = attribute_item.next_named_sibling();
+        while let Some(node) = sibling {
+            if node.kind() != "attribute_item" && !self.kinds().comments.contains(&node.kind()) {
+                let (name, _) =
+                    Self::identifier_name(self.node_text(node.child_by_field_name("name")?));
+                return Some(name);
+            }
+            sibling = node.next_named_sibling();
+        }
+        None
+    }
+    // `attribute_item`s written above an item, in source order
+    fn item_attributes<'t>(&self, item_node: Node<'t>) -> Vec<Node<'t>> {
+        let mut attributes = Vec::new();
+        let mut sibling = item_node.prev_sibling();
+        while let Some(node) = sibling {
+            match node.kind() {
+                "attribute_item" => attributes.push(node),
+                "line_comment" | "block_comment" => {}
+                _ => break,
+            }
+            sibling = node.prev_sibling();
+        }
+        attributes.reverse();
+        attributes
+    }
+    // The path and argument text (`("cfg", Some("(test)"))`) of each of the
+    // item's attributes
+    fn attribute_parts(&self, item_node: Node) -> Vec<(&str, Option<&str>)> {
+        self.item_attributes(item_node)
+            .into_iter()
+            .filter_map(|attribute_item| attribute_item.named_child(0))
+            .filter_map(|attribute| {
+                let path = attribute.named_child(0)?;
+                let arguments = attribute.child_by_field_name("arguments");
+                Some((self.node_text(path), arguments.map(|n| self.node_text(n))))
+            })
+            .collect()
+    }
+    // `#[test]`, or a runtime's `#[tokio::test]`, `#[async_std::test]`, ...
+    fn is_test_function(&self, function_node: Node) -> bool {
+        self.attribute_parts(function_node)
+            .iter()
+            .any(|(path, _)| *path == "test" || path.ends_with("::test"))
+    }
+    // `#[cfg(test)]`, or any predicate that holds under test, such as
+    // `cfg(all(test, feature = "x"))`; `cfg(not(test))` doesn't count
+    fn is_cfg_test(&self, item_node: Node) -> bool {
+        self.item_attributes(item_node)
+            .into_iter()
+            .filter_map(|attribute_item| attribute_item.named_child(0))
+            .any(|attribute| {
+                let is_cfg = attribute
+                    .named_child(0)
+                    .is_some_and(|path| self.node_text(path) == "cfg");
+                let arguments = attribute.child_by_field_name("arguments");
+                is_cfg && arguments.is_some_and(|predicate| self.requires_test(predicate))
+            })
+    }
+    // Whether a cfg predicate's token tree names `test` outside a `not(..)`
+    fn requires_test(&self, predicate: Node) -> bool {
+        let mut negated = false;
+        for token in predicate.children(&mut predicate.walk()) {
+            match token.kind() {
+                "identifier" => {
+                    let name = self.node_text(token);
+                    if name == "test" {
+                        return true;
+                    }
+                    negated = name == "not";
+                }
+                "token_tree" if !negated => {
+                    if self.requires_test(token) {
+                        return true;
+                    }
+                }
+                _ => negated = false,
+            }
+        }
+        false
+    }
+    // Path and arguments of one `#[...]` item
+    fn attribute_info(&self, attribute_item: Node) -> AttributeInfo {
+        let attribute = attribute_item.named_child(0);
+        AttributeInfo {
+            attribute: self.node_text(attribute_item).to_string(),
+            path: attribute
+                .and_then(|attribute| attribute.named_child(0))
+                .map(|path| self.node_text(path).to_string()),
+            args: attribute
+                .map(|attribute| self.attribute_args(attribute))
+                .unwrap_or_default(),
+        }
+    }
+    // The comma-separated arguments of an attribute as `{ key, value }`
+    // pairs: `rename = "x"` has key "rename" and value "x", while `Clone` in
+    // `derive(Clone)` or `test` in `cfg(test)` has a null key. The
+    // `#[path = "x"]` form is a single keyless value.
+    fn attribute_args(&self, attribute: Node) -> Vec<AttributeArg> {
+        let arg = |key: Option<&str>, value: &str| AttributeArg {
+            key: key.map(String::from),
+            value: value.to_string(),
+        };
+        if let Some(value) = attribute.child_by_field_name("value") {
+            return vec![arg(None, self.literal_value(value))];
+        }
+        let Some(arguments) = attribute.child_by_field_name("arguments") else {
+            return Vec::new();
+        };
+        let tokens: Vec<Node> = arguments.children(&mut arguments.walk()).collect();
+        // Drop the enclosing delimiters, then split on top-level commas
+        let inner = tokens.get(1..tokens.len().saturating_sub(1)).unwrap_or(&[]);
+        inner
+            .split(|token| token.kind() == ",")
+            .filter(|group| !group.is_empty())
+            .map(
+                |group| match group.iter().position(|token| token.kind() == "=") {
+                    Some(eq) if eq > 0 && eq + 1 < group.len() => arg(
+                        Some(self.tokens_text(&group[..eq])),
+                        match &group[eq + 1..] {
+                            [literal] => self.literal_value(*literal),
+                            rest => self.tokens_text(rest),
+                        },
+                    ),
+                    _ => arg(None, self.tokens_text(group)),
+                },
+            )
+            .collect()
+    }
+    // Source text spanning a run of sibling tokens
+    fn tokens_text(&self, tokens: &[Node]) -> &str {
+        match (tokens.first(), tokens.last()) {
+            (Some(first), Some(last)) => self.source_slice(first.start_byte()..last.end_byte()),
+            _ => "",
+        }
+    }
+    // The contents of a string literal without its quotes; other nodes as written
+    fn literal_value(&self, node: Node) -> &str {
+        if node.kind() != "string_literal" {
+            return self.node_text(node);
+        }
+        let Some(content) = node.named_child(0) else {
+            return "";
+        };
+        self.source_slice(content.start_byte()..node.end_byte() - 1)
+    }
+    // The layout hints of the item's `#[repr(...)]` attributes, as written:
+    // `C`, `packed(2)`, `align(8)`, `u8`, `transparent`
+    fn repr_hints(&self, item_node: Node) -> Vec<String> {
+        self.item_attributes(item_node)
+            .into_iter()
+            .map(|attribute| self.attribute_info(attribute))
+            .filter(|attribute| attribute.path.as_deref() == Some("repr"))
+            .flat_map(|attribute| attribute.args.into_iter().map(|arg| arg.value))
+            .collect()
+    }
+    // Trait paths listed in the item's `#[derive(...)]` attributes
+    fn derived_traits(&self, item_node: Node) -> Vec<String> {
+        let mut traits = Vec::new();
+        for attribute_item in self.item_attributes(item_node) {
+            let Some(attribute) = attribute_item.named_child(0) else {
+                continue;
+            };
+            let is_derive = attribute
+                .named_child(0)
+                .is_some_and(|path| self.node_text(path) == "derive");
+            if is_derive {
+                traits.extend(
+                    self.attribute_args(attribute)
+                        .into_iter()
+                        .map(|arg| arg.value),
+                );
+            }
+        }
+        traits
+    }
+    pub(crate) fn kinds(&self) -> &'static NodeKinds {
+        self.language.kinds()
+    }
+    // A function's source, minus its comments under `set_strip_comments`.
+    // A comment alone on its line takes the line with it; a trailing one
+    // takes the spaces before it; everything else keeps its spacing.
+    fn body_text(&self, node: Node) -> String {
+        let text = self.source_slice(node.byte_range());
+        if !self.strip_comments || text.is_empty() {
+            return text.to_string();
+        }
+        let mut comments = Vec::new();
+        self.collect_comments(node, &mut comments);
+        let bytes = text.as_bytes();
+        let is_blank = |b: &u8| *b == b' ' || *b == b'\t';
+        let mut body = String::new();
+        let mut copied = 0;
+        for comment in comments {
+            let (mut start, mut end) = (
+                comment.start - node.start_byte(),
+                comment.end - node.start_byte(),
+            );
+            let spaces_start = bytes[..start]
+                .iter()
+                .rposition(|b| !is_blank(b))
+                .map_or(0, |i| i + 1);
+            let alone_on_line = spaces_start == 0 || bytes[spaces_start - 1] == b'\n';
+            // Doc comments end with their newline, plain ones just before it
+            let ends_line = bytes[end - 1] == b'\n';
+            let after = if ends_line {
+                end
+            } else {
+                end + bytes[end..].iter().take_while(|b| is_blank(b)).count()
+            };
+            if ends_line || after == bytes.len() || bytes[after] == b'\n' {
+                start = spaces_start;
+                end = after;
+                if alone_on_line && !ends_line && end < bytes.len() {
+                    end += 1;
+                } else if !alone_on_line && ends_line {
+                    end -= 1;
+                }
+            }
+            if start < copied {
+                continue;
+            }
+            body.push_str(&text[copied..start]);
+            copied = end;
+        }
+        body.push_str(&text[copied..]);
+        body
+    }
+    // Function items at any depth: top level, in impls, traits and modules,
+    // and nested inside other functions
+    fn collect_functions<'t>(&self, node: Node<'t>, functions: &mut Vec<Node<'t>>) {
+        for child in node.children(&mut node.walk()) {
+            if self.kinds().functions.contains(&child.kind()) {
+                functions.push(child);
+            }
+            self.collect_functions(child, functions);
+        }
+    }
+    // The name a raw identifier stands for, and whether it was written raw:
+    // `r#type` gives `("type", true)`, `kind` gives `("kind", false)`
+    fn identifier_name(text: &str) -> (&str, bool) {
+        match text.strip_prefix("r#") {
+            Some(name) => (name, true),
+            None => (text, false),
+        }
+    }
+    // How many `#`s delimit a raw string literal (`r"…"` has 0, `br##"…"##`
+    // has 2), or `None` when `text` isn't a raw string
+    fn raw_string_hashes(text: &str) -> Option<usize> {
+        let rest = text.trim_start_matches(['b', 'c']).strip_prefix('r')?;
+        let hashes = rest.len() - rest.trim_start_matches('#').len();
+        rest[hashes..].starts_with('"').then_some(hashes)
+    }
+    fn collect_comments(&self, node: Node, comments: &mut Vec<Range<usize>>) {
+        for child in node.children(&mut node.walk()) {
+            if self.kinds().comments.contains(&child.kind()) {
+                comments.push(child.byte_range());
+            } else {
+                self.collect_comments(child, comments);
+            }
+        }
+    }
+    /// The source text of `node`, for an [`Extractor`] to read the tree by.
+    pub fn node_text(&self, node: Node) -> &str {
+        self.source_slice(node.byte_range())
+    }
+    // Slicing checks char boundaries instead of panicking: a range that
+    // splits a UTF-8 character yields an empty string and a warning
+    fn source_slice(&self, range: Range<usize>) -> &str {
+        match self.code.get(range.clone()) {
+            Some(text) => text,
+            None => {
+                eprintln!(
+                    "Warning: byte range {}..{} is not on a UTF-8 boundary",
+                    range.start, range.end
+                );
+                ""
+            }
+        }
+    }
+}
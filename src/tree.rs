@@ -0,0 +1,344 @@
+use serde::{Deserialize, Serialize};
+use std::str::FromStr;
+use tree_sitter::{Node, TreeCursor};
+
+use crate::language::{Language, NodeKinds};
+use crate::service::ASTConversionService;
+
+/// One node of the recursive tree built by `--mode tree`.
+#[derive(Default, Serialize, Deserialize, Debug)]
+pub struct Thing {
+    pub kind: Kind,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    pub text: String,
+    // An `If`'s condition, `x > 0` or `let Some(y) = y`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub condition: Option<String>,
+    // The tree-sitter kind of a node kept by `include_unknown`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub raw_kind: Option<String>,
+    // Set when `max_depth` stopped the builder from descending into the
+    // node's children, or into those of a dropped node under it
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub truncated: bool,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub children: Vec<Thing>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub relations: Vec<String>, // Simplified relation representation
+}
+
+impl Thing {
+    pub fn new(kind: Kind, text: String) -> Self {
+        Thing {
+            kind,
+            name: None,
+            text,
+            condition: None,
+            raw_kind: None,
+            truncated: false,
+            children: Vec::new(),
+            relations: Vec::new(),
+        }
+    }
+}
+
+impl Thing {
+    /// The tree as one pre-order list, each node pointing at its container
+    /// by `parent_id`; the root comes first, with id 0 and no parent.
+    pub fn flatten(self) -> Vec<FlatThing> {
+        let mut flat = Vec::new();
+        self.flatten_into(None, &mut flat);
+        flat
+    }
+
+    fn flatten_into(self, parent_id: Option<usize>, flat: &mut Vec<FlatThing>) {
+        let id = flat.len();
+        flat.push(FlatThing {
+            id,
+            parent_id,
+            kind: self.kind,
+            name: self.name,
+            text: self.text,
+            condition: self.condition,
+            raw_kind: self.raw_kind,
+            truncated: self.truncated,
+            relations: self.relations,
+        });
+        for child in self.children {
+            child.flatten_into(Some(id), flat);
+        }
+    }
+}
+
+/// One node of [`Thing::flatten`]: a [`Thing`] without its children, which
+/// point back at it instead.
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+pub struct FlatThing {
+    pub id: usize,
+    pub parent_id: Option<usize>,
+    pub kind: Kind,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    pub text: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub condition: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub raw_kind: Option<String>,
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub truncated: bool,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub relations: Vec<String>,
+}
+
+/// Kinds of tree node; children are ordered by this, then by name.
+#[derive(Ord, PartialOrd, Eq, PartialEq, Clone, Copy, Default, Serialize, Deserialize, Debug)]
+pub enum Kind {
+    Root,
+    Comment,
+    DocComment,
+    Import,
+    Module,
+    Struct,
+    Enum,
+    Derive,
+    Function,
+    Method,
+    Field,
+    Variant,
+    Variable,
+    Type,
+    Trait,
+    Impl,
+    If,
+    Else,
+    Loop,
+    Tuple,
+    Array,
+    FunctionCall,
+    // Also the default, so a defaulted `Thing` is never mistaken for a
+    // real comment
+    #[default]
+    Undefined,
+}
+
+// Rust's node kinds; the tree builder reads the parsed language's own from
+// its `NodeKinds`
+impl FromStr for Kind {
+    type Err = ();
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(Kind::of(s, Language::Rust.kinds()))
+    }
+}
+
+impl Kind {
+    pub(crate) fn of(node_kind: &str, kinds: &NodeKinds) -> Kind {
+        kinds
+            .tree
+            .iter()
+            .find(|(tree_kind, _)| *tree_kind == node_kind)
+            .map_or(Kind::Undefined, |(_, kind)| *kind)
+    }
+    pub fn is_undefined(&self) -> bool {
+        matches!(self, Kind::Undefined)
+    }
+    // `///`, `//!`, `/**` and `/*!` comments are docs; `////` and `/***`
+    // are ordinary comments, as in rustc
+    fn is_doc_comment(text: &str) -> bool {
+        let outer_line = text.starts_with("///") && !text.starts_with("////");
+        let outer_block = text.starts_with("/**") && !text.starts_with("/***") && text != "/**/";
+        outer_line || outer_block || text.starts_with("//!") || text.starts_with("/*!")
+    }
+}
+
+/// Knobs for [`ASTConversionService::generate_tree`].
+#[derive(Debug, Clone)]
+pub struct TreeOptions {
+    /// Keep named nodes with no [`Kind`] mapping as `Kind::Undefined`, with
+    /// their tree-sitter kind in `raw_kind`, instead of dropping them.
+    pub include_unknown: bool,
+    /// How many levels of syntax nodes to descend before marking a node
+    /// `truncated`, so pathologically nested input can't overflow the stack.
+    pub max_depth: usize,
+    /// Give functions, impls and other items with a body only the text
+    /// before it, and the root no text.
+    pub exclude_bodies: bool,
+}
+
+impl TreeOptions {
+    pub const DEFAULT_MAX_DEPTH: usize = 512;
+}
+
+impl Default for TreeOptions {
+    fn default() -> Self {
+        TreeOptions {
+            include_unknown: false,
+            max_depth: Self::DEFAULT_MAX_DEPTH,
+            exclude_bodies: false,
+        }
+    }
+}
+
+impl ASTConversionService {
+    /// The whole file as a recursive tree of [`Thing`]s rooted at `Kind::Root`.
+    pub fn generate_ast_with_relations(&self) -> Thing {
+        self.generate_tree(&TreeOptions::default())
+    }
+    /// Like `generate_ast_with_relations`, with the given options.
+    pub fn generate_tree(&self, options: &TreeOptions) -> Thing {
+        let mut ast_root = Thing::new(Kind::Root, "Root".to_string());
+        ast_root.truncated = self.build_ast(self.tree.root_node(), &mut ast_root, options, 0);
+        Self::sort_children(&mut ast_root);
+        ast_root
+    }
+    /// The file's top-level items one at a time, each built only when the
+    /// iterator reaches it, so a caller can serialize a file of any size
+    /// without holding the whole tree.
+    pub fn items(&self) -> impl Iterator<Item = Thing> + '_ {
+        self.items_with(TreeOptions::default())
+    }
+    /// Like `items`, with the given options.
+    pub fn items_with(&self, options: TreeOptions) -> impl Iterator<Item = Thing> + '_ {
+        let mut cursor = self.tree.root_node().walk();
+        let exhausted = !cursor.goto_first_child();
+        Items {
+            service: self,
+            cursor,
+            options,
+            exhausted,
+            pending: Vec::new(),
+        }
+    }
+
+    fn add_parent_name(name: &str, parent: &mut Thing) {
+        if parent.name.is_none() {
+            parent.name = Some(name.to_string());
+        }
+    }
+
+    fn parent_namer(node_kind: &str, body: &str, parent: &mut Thing) {
+        if matches!(
+            node_kind,
+            // JavaScript names methods with a `property_identifier`
+            "type_identifier" | "identifier" | "field_identifier" | "property_identifier"
+        ) {
+            Self::add_parent_name(body, parent);
+        }
+    }
+
+    // Main function to iterate through the items in the file. Returns
+    // whether `max_depth` cut off part of a node that was dropped, so the
+    // nearest kept ancestor is marked `truncated` in its place.
+    fn build_ast(
+        &self,
+        node: Node,
+        parent: &mut Thing,
+        options: &TreeOptions,
+        depth: usize,
+    ) -> bool {
+        let node_kind = node.kind().to_string();
+        let body = self.thing_text(node, options);
+        Self::parent_namer(&node_kind, &body, parent);
+
+        let mut kind = Kind::of(&node_kind, self.kinds());
+        if kind == Kind::Comment && Kind::is_doc_comment(&body) {
+            kind = Kind::DocComment;
+        }
+        let mut element = Thing::new(kind, body);
+        if kind == Kind::If {
+            element.condition = node
+                .child_by_field_name("condition")
+                .map(|condition| self.node_text(condition).to_string());
+        }
+
+        let mut cut = false;
+        if depth >= options.max_depth {
+            cut = node.child_count() > 0;
+        } else {
+            for child in node.children(&mut node.walk()) {
+                cut |= self.build_ast(child, &mut element, options, depth + 1);
+            }
+        }
+
+        element.truncated = cut;
+        if !element.kind.is_undefined() {
+            parent.children.push(element);
+        } else if options.include_unknown && node.is_named() {
+            element.raw_kind = Some(node_kind);
+            parent.children.push(element);
+        } else {
+            // Blocks, item lists and other unmapped containers are dropped,
+            // but what they hold (a function's `let`s and calls, a struct's
+            // fields, a module's items) moves up to the nearest kept node
+            parent.children.append(&mut element.children);
+            return cut;
+        }
+        false
+    }
+
+    // A node's source, or under `exclude_bodies` just the part before its
+    // body (`fn f() -> u8`, `impl Display for S`), and nothing for the whole
+    // file, whose items are all children anyway
+    fn thing_text(&self, node: Node, options: &TreeOptions) -> String {
+        if !options.exclude_bodies {
+            self.node_text(node).to_string()
+        } else if node.child_by_field_name("body").is_some() {
+            self.signature_text(node)
+        } else if node.parent().is_none() {
+            String::new()
+        } else {
+            self.node_text(node).to_string()
+        }
+    }
+
+    // Order every node's children by kind, then name, once the whole tree is
+    // built; sorting inside `build_ast` re-sorted a parent per appended child
+    fn sort_children(thing: &mut Thing) {
+        for child in &mut thing.children {
+            Self::sort_children(child);
+        }
+        thing.children.sort_by(|a, b| {
+            a.kind.cmp(&b.kind).then_with(|| {
+                a.name
+                    .as_deref()
+                    .unwrap_or("")
+                    .cmp(b.name.as_deref().unwrap_or(""))
+            })
+        });
+    }
+}
+
+// Walks the root's children with a cursor, building one item per step
+struct Items<'a> {
+    service: &'a ASTConversionService,
+    cursor: TreeCursor<'a>,
+    options: TreeOptions,
+    exhausted: bool,
+    // Items already built from the current sibling, last first: an unmapped
+    // one (a `const` holding an array) hands up what it holds instead
+    pending: Vec<Thing>,
+}
+
+impl Iterator for Items<'_> {
+    type Item = Thing;
+    fn next(&mut self) -> Option<Thing> {
+        loop {
+            if let Some(mut item) = self.pending.pop() {
+                ASTConversionService::sort_children(&mut item);
+                return Some(item);
+            }
+            if self.exhausted {
+                return None;
+            }
+            let node = self.cursor.node();
+            self.exhausted = !self.cursor.goto_next_sibling();
+            // Nodes `build_ast` drops with nothing mapped under them
+            // (punctuation, most unmapped kinds) leave the holder empty;
+            // move on to the next sibling
+            let mut holder = Thing::default();
+            self.service.build_ast(node, &mut holder, &self.options, 1);
+            self.pending = holder.children;
+            self.pending.reverse();
+        }
+    }
+}
@@ -0,0 +1,37 @@
+use treesitter::ASTConversionService;
+
+#[test]
+fn test_modules_are_found_by_their_cfg_predicate() {
+    let code = r#"
+#[cfg(test)]
+mod plain {}
+#[cfg( test )]
+mod spaced {}
+#[cfg(all(test, feature = "x"))]
+mod combined {}
+#[cfg(not(test))]
+mod negated {}
+#[cfg(feature = "test")]
+mod feature {}
+"#;
+    let service = ASTConversionService::new(code.to_string()).unwrap();
+    let flags: Vec<(String, bool)> = service
+        .modules_and_impls()
+        .iter()
+        .map(|module| {
+            let name = module["name"].as_str().unwrap().to_string();
+            (name, module["is_cfg_test"].as_bool().unwrap())
+        })
+        .collect();
+    let flag = |name: &str, is_test| (name.to_string(), is_test);
+    assert_eq!(
+        flags,
+        [
+            flag("plain", true),
+            flag("spaced", true),
+            flag("combined", true),
+            flag("negated", false),
+            flag("feature", false),
+        ]
+    );
+}
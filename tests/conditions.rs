@@ -0,0 +1,38 @@
+use treesitter::{ASTConversionService, Kind, Thing};
+
+// The one child of `kind`; calls in conditions and branches sit beside it
+fn only_child(thing: &Thing, kind: Kind) -> &Thing {
+    let mut children = thing.children.iter().filter(|child| child.kind == kind);
+    let child = children.next().expect("no child of that kind");
+    assert!(children.next().is_none(), "{:?}", thing.children);
+    child
+}
+
+#[test]
+fn else_branches_nest_under_their_if() {
+    let code = r#"
+fn sign(x: i32) -> i32 {
+    if x > 0 {
+        1
+    } else if let Some(y) = x.checked_neg() {
+        y.signum()
+    } else {
+        0
+    }
+}
+"#;
+    let service = ASTConversionService::new(code.to_string()).unwrap();
+    let root = service.generate_ast_with_relations();
+    let function = only_child(only_child(&root, Kind::Root), Kind::Function);
+
+    let outer = only_child(function, Kind::If);
+    assert_eq!(outer.condition.as_deref(), Some("x > 0"));
+    let inner = only_child(only_child(outer, Kind::Else), Kind::If);
+    assert_eq!(
+        inner.condition.as_deref(),
+        Some("let Some(y) = x.checked_neg()")
+    );
+    let last = only_child(inner, Kind::Else);
+    assert!(last.children.is_empty());
+    assert_eq!(last.condition, None);
+}
@@ -0,0 +1,24 @@
+use treesitter::ASTConversionService;
+
+#[test]
+fn generic_impls_connect_to_their_type() {
+    let code = r#"
+mod shapes {
+    pub struct A<'a, T, const N: usize>(&'a T);
+    impl<T> std::fmt::Debug for A<'_, T, 3> {}
+}
+"#;
+    let service = ASTConversionService::new(code.to_string()).unwrap();
+    let dot = service.to_dot();
+    let lines: Vec<&str> = dot.lines().map(str::trim).collect();
+    assert_eq!(
+        lines,
+        [
+            "digraph relations {",
+            r#""A" [label="A", shape=box];"#,
+            r#""Debug" [label="Debug", style=dashed];"#,
+            r#""Debug" -> "A" [label="implements"];"#,
+            "}",
+        ]
+    );
+}
@@ -0,0 +1,25 @@
+use serde_json::json;
+use treesitter::ASTConversionService;
+
+#[test]
+fn edges_name_types_bare_and_reach_into_modules() {
+    let code = r#"
+struct A<'a, T, const N: usize>(&'a T);
+impl<T> std::fmt::Debug for A<'_, T, 3> {}
+
+mod m {
+    #[derive(Clone)]
+    pub struct S;
+    impl Debug for S {}
+}
+"#;
+    let service = ASTConversionService::new(code.to_string()).unwrap();
+    assert_eq!(
+        service.edges(),
+        [
+            json!({ "from": "Debug", "to": "A", "kind": "implements" }),
+            json!({ "from": "S", "to": "Clone", "kind": "derives" }),
+            json!({ "from": "Debug", "to": "S", "kind": "implements" }),
+        ]
+    );
+}
@@ -0,0 +1,12 @@
+use treesitter::ASTConversionService;
+
+#[test]
+fn exclude_bodies_applies_inside_modules() {
+    let code = "mod m { fn f() { let x = 1; } }";
+    let mut service = ASTConversionService::new(code.to_string()).unwrap();
+    service.set_exclude_bodies(true);
+    let modules = service.modules_and_impls();
+    let function = &modules[0]["children"][0];
+    assert_eq!(function["name"], "f");
+    assert!(function.get("body").is_none());
+}
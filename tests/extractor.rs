@@ -0,0 +1,58 @@
+use serde_json::{json, Value};
+use treesitter::{ASTConversionService, Extractor, Node};
+
+// Every `unsafe { ... }` block in the file, by its source
+struct UnsafeBlocks;
+
+impl Extractor for UnsafeBlocks {
+    fn key(&self) -> &str {
+        "unsafe_blocks"
+    }
+    fn extract(&self, service: &ASTConversionService, root: Node) -> Value {
+        fn collect(service: &ASTConversionService, node: Node, found: &mut Vec<String>) {
+            if node.kind() == "unsafe_block" {
+                found.push(service.node_text(node).to_string());
+            }
+            for child in node.children(&mut node.walk()) {
+                collect(service, child, found);
+            }
+        }
+        let mut found = Vec::new();
+        collect(service, root, &mut found);
+        json!(found)
+    }
+}
+
+struct NoDocs;
+
+impl Extractor for NoDocs {
+    fn key(&self) -> &str {
+        "docs"
+    }
+    fn extract(&self, _service: &ASTConversionService, _root: Node) -> Value {
+        json!("replaced")
+    }
+}
+
+#[test]
+fn added_extractors_join_the_output() {
+    let code = r#"
+//! Crate docs
+fn read(p: *const u8) -> u8 {
+    unsafe { *p }
+}
+"#;
+    let mut service = ASTConversionService::new(code.to_string()).unwrap();
+    assert!(service.generate_json().get("unsafe_blocks").is_none());
+
+    service.add_extractor(UnsafeBlocks);
+    service.add_extractor(NoDocs);
+    let json = service.generate_json();
+    assert_eq!(json["unsafe_blocks"], json!(["unsafe { *p }"]));
+    assert_eq!(json["docs"], "replaced");
+    assert_eq!(json["functions"][0]["name"], "read");
+
+    let only = service.generate_json_for(&["unsafe_blocks"]);
+    assert_eq!(only.as_object().unwrap().len(), 1);
+    assert_eq!(service.stats()["counts"]["unsafe_blocks"], 1);
+}
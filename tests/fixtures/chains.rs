@@ -0,0 +1,11 @@
+async fn load(client: &Client, ids: &[u32]) -> Result<Vec<String>, Error> {
+    let request = Request::builder().uri("/items").header("a", "b").build()?;
+    let body = client.send(request).await?.text().await?;
+    let names = ids
+        .iter()
+        .filter(|id| lookup(**id).is_some())
+        .map(|id| format!("{}", id).trim().to_owned())
+        .collect::<Vec<_>>();
+    names.len();
+    Ok(names)
+}
@@ -0,0 +1,8 @@
+use std::error::Error;
+use std::fmt::Write;
+
+pub fn render(out: &mut dyn Write, hooks: Vec<Box<dyn Fn(&str) -> String + Send>>) {}
+
+pub fn lines(text: &str) -> impl Iterator<Item = Result<&str, Box<dyn Error>>> + '_ {
+    text.lines().map(Ok)
+}
@@ -0,0 +1,17 @@
+#[derive(Debug)]
+pub enum Shape {
+    Circle { radius: f64 },
+    Rect(f64, f64),
+    Empty,
+}
+
+#[repr(u8)]
+enum Level {
+    Low = 1,
+    High = 10,
+}
+
+enum Tree<T> {
+    Leaf(T),
+    Node(Box<Tree<T>>, Box<Tree<T>>),
+}
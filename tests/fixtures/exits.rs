@@ -0,0 +1,13 @@
+fn first_even(path: &str) -> Result<Option<u32>, std::io::Error> {
+    let text = std::fs::read_to_string(path)?;
+    for line in text.lines() {
+        let Ok(value) = line.parse::<u32>() else {
+            continue;
+        };
+        if value % 2 == 0 {
+            return Ok(Some(value));
+        }
+    }
+    let _ignored = || -> Option<u32> { Some("1".parse().ok()?) };
+    Ok(None)
+}
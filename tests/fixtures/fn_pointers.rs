@@ -0,0 +1,11 @@
+pub type Callback = extern "system" fn(i32) -> i32;
+
+pub struct Handlers {
+    pub on_event: extern "C" fn(u32),
+    legacy: unsafe extern fn(),
+    plain: fn() -> bool,
+}
+
+pub struct Raw(extern "C-unwind" fn());
+
+pub fn register(callback: extern "C" fn(*mut u8), fallback: fn()) {}
@@ -0,0 +1,21 @@
+/// Largest item, or `None` when empty
+pub fn largest<'a, T: PartialOrd>(items: &'a [T]) -> Option<&'a T> {
+    let mut best = items.first()?;
+    for item in items {
+        if item > best {
+            best = item;
+        }
+    }
+    Some(best)
+}
+
+pub(crate) fn fill<T, const N: usize>(value: T) -> [T; N]
+where
+    T: Copy + Default,
+{
+    [value; N]
+}
+
+async fn fetch<U = String>(url: U) -> Result<U, ()> {
+    Ok(url)
+}
@@ -0,0 +1,30 @@
+use std::fmt;
+
+pub struct Counter {
+    count: u32,
+}
+
+impl Counter {
+    pub fn new() -> Self {
+        Counter { count: 0 }
+    }
+
+    pub fn increment(&mut self) -> u32 {
+        self.count += 1;
+        self.count
+    }
+}
+
+impl fmt::Display for Counter {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.count)
+    }
+}
+
+impl<T: Clone> From<Vec<T>> for Counter {
+    fn from(items: Vec<T>) -> Self {
+        Counter { count: items.len() as u32 }
+    }
+}
+
+impl<T> !Send for Wrapper<T> {}
@@ -0,0 +1,11 @@
+#[derive(Clone, Copy)]
+#[repr(C, packed(2))]
+pub struct Header {
+    pub tag: u8,
+    len: u32,
+    #[doc(hidden)]
+    pub checksum: u16,
+}
+
+#[repr(transparent)]
+pub struct Handle(*mut u8);
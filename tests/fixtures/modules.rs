@@ -0,0 +1,23 @@
+//! Module fixture
+
+pub mod shapes {
+    pub struct Square(pub u32);
+
+    pub fn area(square: &Square) -> u32 {
+        square.0 * square.0
+    }
+
+    mod internal {
+        pub(super) const SIDES: u8 = 4;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::shapes::*;
+
+    #[test]
+    fn square_area() {
+        assert_eq!(area(&Square(3)), 9);
+    }
+}
@@ -0,0 +1,14 @@
+fn drain(queue: &mut Vec<(u32, String)>, limit: Option<u32>) -> u32 {
+    let mut total = 0;
+    if let Some(max) = limit {
+        total = max;
+    }
+    while let Some((id, name)) = queue.pop() {
+        match name.len() {
+            0 => {}
+            len if len > 8 => total += len as u32,
+            _ => total += id,
+        }
+    }
+    total
+}
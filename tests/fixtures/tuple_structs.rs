@@ -0,0 +1,11 @@
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Meters(pub f64);
+
+struct Pair<A, B>(A, pub(crate) B);
+
+pub struct Unit;
+
+struct Named {
+    pub id: u64,
+    label: String,
+}
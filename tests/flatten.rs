@@ -0,0 +1,94 @@
+use treesitter::{ASTConversionService, Kind};
+
+#[test]
+fn flattened_nodes_point_at_their_container() {
+    let code = r#"
+struct Point;
+
+fn outer() {
+    fn inner() {}
+}
+"#;
+    let service = ASTConversionService::new(code.to_string()).unwrap();
+    let flat = service.generate_ast_with_relations().flatten();
+
+    assert_eq!(flat[0].kind, Kind::Root);
+    assert_eq!(flat[0].parent_id, None);
+    assert!(flat
+        .iter()
+        .enumerate()
+        .all(|(index, node)| node.id == index));
+
+    let named = |name: &str| {
+        let node = flat.iter().find(|node| node.name.as_deref() == Some(name));
+        node.unwrap()
+    };
+    let file = named("Point").parent_id.unwrap();
+    assert_eq!(flat[file].kind, Kind::Root);
+    assert_eq!(named("outer").parent_id, Some(file));
+    assert_eq!(named("inner").parent_id, Some(named("outer").id));
+}
+
+#[test]
+fn fields_variants_and_methods_sit_under_their_item() {
+    let code = r#"
+enum E { A, B(u8) }
+struct S { x: u8 }
+impl S { fn get(&self) {} }
+"#;
+    let service = ASTConversionService::new(code.to_string()).unwrap();
+    let flat = service.generate_ast_with_relations().flatten();
+
+    let under = |kind: Kind, name: &str| {
+        let parent = flat
+            .iter()
+            .find(|node| node.kind == kind && node.name.as_deref() == Some(name));
+        let parent = parent.unwrap().id;
+        let children = flat.iter().filter(|node| node.parent_id == Some(parent));
+        children
+            .map(|node| (node.kind, node.name.as_deref().unwrap_or("")))
+            .collect::<Vec<_>>()
+    };
+    assert_eq!(under(Kind::Struct, "S"), [(Kind::Field, "x")]);
+    assert_eq!(
+        under(Kind::Enum, "E"),
+        [(Kind::Variant, "A"), (Kind::Variant, "B")]
+    );
+    assert_eq!(under(Kind::Impl, "S"), [(Kind::Function, "get")]);
+}
+
+#[test]
+fn module_items_and_function_bodies_are_kept() {
+    let code = r#"
+mod foo {
+    pub fn a() {
+        let v = g();
+    }
+    fn b() {}
+    struct S {
+        x: u8,
+    }
+}
+"#;
+    let service = ASTConversionService::new(code.to_string()).unwrap();
+    let flat = service.generate_ast_with_relations().flatten();
+
+    let named = |kind: Kind, name: &str| {
+        let node = flat
+            .iter()
+            .find(|node| node.kind == kind && node.name.as_deref() == Some(name));
+        node.unwrap()
+    };
+    let module = named(Kind::Module, "foo").id;
+    assert_eq!(named(Kind::Function, "a").parent_id, Some(module));
+    assert_eq!(named(Kind::Function, "b").parent_id, Some(module));
+    assert_eq!(named(Kind::Struct, "S").parent_id, Some(module));
+    assert_eq!(
+        named(Kind::Field, "x").parent_id,
+        Some(named(Kind::Struct, "S").id)
+    );
+    let binding = named(Kind::Variable, "v");
+    assert_eq!(binding.parent_id, Some(named(Kind::Function, "a").id));
+    let call = named(Kind::FunctionCall, "g");
+    assert_eq!(call.parent_id, Some(binding.id));
+}
@@ -0,0 +1,42 @@
+use treesitter::{code_blocks, Language};
+
+#[test]
+fn only_fences_of_the_language_are_extracted() {
+    let markdown = r#"# Example
+
+```rust
+fn first() {}
+```
+
+  ```rs,no_run
+  struct Indented;
+  ```
+
+```python
+def skipped(): pass
+```
+
+```
+fn untagged() {}
+```
+"#;
+    let blocks = code_blocks(markdown, Language::Rust);
+    assert_eq!(blocks.len(), 2);
+    assert_eq!(blocks[0].start_line, 3);
+    assert_eq!(blocks[0].info, "rust");
+    assert_eq!(blocks[0].code, "fn first() {}\n");
+    assert_eq!(blocks[1].start_line, 7);
+    assert_eq!(blocks[1].info, "rs,no_run");
+    assert_eq!(blocks[1].code, "struct Indented;\n");
+
+    let python = code_blocks(markdown, Language::Python);
+    assert_eq!(python.len(), 1);
+    assert_eq!(python[0].code, "def skipped(): pass\n");
+}
+
+#[test]
+fn an_open_fence_runs_to_the_end() {
+    let blocks = code_blocks("````rust\n```\nfn f() {}\n", Language::Rust);
+    assert_eq!(blocks.len(), 1);
+    assert_eq!(blocks[0].code, "```\nfn f() {}\n");
+}
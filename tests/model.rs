@@ -0,0 +1,31 @@
+use treesitter::{ASTConversionService, BindingKind, CallKind, StructKind};
+
+#[test]
+fn accessors_return_typed_items() {
+    let code = r#"
+pub struct Point(pub i32, i32);
+
+pub fn shift<T: Into<i32>>(point: &Point, by: T) -> Point {
+    let dx = by.into();
+    Point(point.0 + dx, point.1)
+}
+"#;
+    let service = ASTConversionService::new(code.to_string()).unwrap();
+
+    let functions = service.functions();
+    let shift = &functions[0];
+    assert_eq!(shift.name, "shift");
+    assert_eq!(
+        shift.signature,
+        "pub fn shift<T: Into<i32>>(point: &Point, by: T) -> Point"
+    );
+    assert!(shift.parameters[0].is_reference);
+    assert_eq!(shift.called_methods[0].kind, CallKind::Method);
+    assert_eq!(shift.local_variables[0].kind, BindingKind::Let);
+    assert_eq!(shift.position.start_line, 3);
+
+    let structs = service.structs();
+    assert_eq!(structs[0].struct_kind, Some(StructKind::Tuple));
+    assert_eq!(structs[0].fields[1].index, 1);
+    assert_eq!(structs[0].fields[1].ty.as_deref(), Some("i32"));
+}
@@ -0,0 +1,26 @@
+use treesitter::ASTConversionService;
+
+#[test]
+fn items_get_their_path_through_the_files_modules() {
+    let code = r#"
+pub struct Config;
+
+pub mod outer {
+    pub mod inner {
+        pub fn build() {
+            fn helper() {}
+        }
+    }
+}
+"#;
+    let mut service = ASTConversionService::new(code.to_string()).unwrap();
+    assert_eq!(service.structs()[0].path, None);
+
+    service.set_module_paths(true);
+    assert_eq!(service.structs()[0].path.as_deref(), Some("Config"));
+
+    let json = service.generate_json();
+    let build = &json["modules_and_impls"][0]["children"][0]["children"][0];
+    assert_eq!(build["path"], "outer::inner::build");
+    assert!(build["nested_functions"][0].get("path").is_none());
+}
@@ -0,0 +1,58 @@
+use treesitter::{ASTConversionService, Language};
+
+fn parameters(code: &str, language: Language) -> Vec<(String, Option<String>, Option<String>)> {
+    let service = ASTConversionService::with_language(code.to_string(), language).unwrap();
+    let functions = service.functions();
+    let parameters = functions[0].parameters.iter();
+    parameters
+        .map(|p| (p.name.clone(), p.ty.clone(), p.default_value.clone()))
+        .collect()
+}
+
+fn parameter(
+    name: &str,
+    ty: Option<&str>,
+    default: Option<&str>,
+) -> (String, Option<String>, Option<String>) {
+    (
+        name.to_string(),
+        ty.map(String::from),
+        default.map(String::from),
+    )
+}
+
+#[test]
+fn python_parameters_have_their_names_and_defaults() {
+    let code = "def f(x=1, *args, y: int = 2, *, z, **kw):\n    pass\n";
+    assert_eq!(
+        parameters(code, Language::Python),
+        [
+            parameter("x", None, Some("1")),
+            parameter("args", None, None),
+            parameter("y", Some("int"), Some("2")),
+            parameter("z", None, None),
+            parameter("kw", None, None),
+        ]
+    );
+}
+
+#[test]
+fn javascript_parameters_have_their_names_and_defaults() {
+    let code = "function g(a, b = 2, ...rest) {}";
+    assert_eq!(
+        parameters(code, Language::JavaScript),
+        [
+            parameter("a", None, None),
+            parameter("b", None, Some("2")),
+            parameter("rest", None, None),
+        ]
+    );
+}
+
+#[test]
+fn other_languages_keep_their_own_signatures() {
+    let code = "def f(x=1):\n    pass\nclass C:\n    pass\n";
+    let service = ASTConversionService::with_language(code.to_string(), Language::Python).unwrap();
+    assert_eq!(service.functions()[0].signature, "def f(x=1)");
+    assert_eq!(service.structs()[0].struct_kind, None);
+}
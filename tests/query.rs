@@ -0,0 +1,26 @@
+use treesitter::{ASTConversionService, ConversionError};
+
+#[test]
+fn queries_run_against_the_parsed_tree() {
+    let code = "fn alpha() {}\nstruct Beta;\nfn gamma() {}\n";
+    let service = ASTConversionService::new(code.to_string()).unwrap();
+
+    let captures = service
+        .query("(function_item name: (identifier) @name)")
+        .unwrap();
+    let names: Vec<&str> = captures.iter().map(|c| c.text.as_str()).collect();
+    assert_eq!(names, ["alpha", "gamma"]);
+    assert_eq!(captures[1].capture, "name");
+    assert_eq!(captures[1].position.start_line, 2);
+
+    let captures = service
+        .query("(struct_item name: (type_identifier) @s) (function_item) @f")
+        .unwrap();
+    let patterns: Vec<usize> = captures.iter().map(|c| c.pattern).collect();
+    assert_eq!(patterns, [1, 0, 1]);
+
+    assert!(matches!(
+        service.query("(function_item"),
+        Err(ConversionError::Query(_))
+    ));
+}
@@ -0,0 +1,69 @@
+use serde_json::json;
+use treesitter::parse_to_json;
+
+#[test]
+fn raw_identifiers_are_reported_without_their_prefix() {
+    let code = r#"
+struct r#Match {
+    r#type: u8,
+    kind: u8,
+}
+
+enum Token {
+    r#Self,
+    Word,
+}
+
+fn r#fn(r#in: u8, out: u8) {}
+
+fn caller() {
+    r#fn(1, 2);
+}
+"#;
+    let json = parse_to_json(code).unwrap();
+
+    let structure = &json["structs"][0];
+    assert_eq!(structure["name"], "Match");
+    assert_eq!(structure["raw"], true);
+    assert_eq!(structure["fields"][0]["name"], "type");
+    assert_eq!(structure["fields"][0]["raw"], true);
+    assert_eq!(structure["fields"][1]["name"], "kind");
+    assert_eq!(structure["fields"][1]["raw"], false);
+
+    let variants = &json["enums"][0]["variants"];
+    assert_eq!(variants[0]["name"], "Self");
+    assert_eq!(variants[0]["raw"], true);
+    assert_eq!(variants[1]["raw"], false);
+
+    let function = &json["functions"][0];
+    assert_eq!(function["name"], "fn");
+    assert_eq!(function["raw"], true);
+    assert_eq!(function["parameters"][0]["name"], "in");
+    assert_eq!(function["parameters"][0]["raw"], true);
+    assert_eq!(function["parameters"][1]["raw"], false);
+    assert_eq!(json["functions"][1]["raw"], false);
+
+    // Calls to a raw name still resolve to the function defined here
+    assert_eq!(json["call_graph"][0]["from"], "caller");
+    assert_eq!(json["call_graph"][0]["to"], "fn");
+}
+
+#[test]
+fn raw_strings_record_their_hash_count() {
+    let code = r###"
+const PLAIN: &str = "r#not raw";
+const NO_HASHES: &str = r"C:\path";
+const ONE: &str = r#"say "hi""#;
+const TWO: &[u8] = br##"a "# b"##;
+"###;
+    let json = parse_to_json(code).unwrap();
+    let hashes: Vec<_> = json["literals"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|literal| literal["raw_hashes"].clone())
+        .collect();
+
+    assert_eq!(hashes, [json!(null), json!(0), json!(1), json!(2)]);
+    assert_eq!(json["literals"][2]["value"], r##"r#"say "hi""#"##);
+}
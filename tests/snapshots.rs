@@ -0,0 +1,13 @@
+use std::fs;
+
+use treesitter::parse_to_json;
+
+// One snapshot of the full `generate_json` document per fixture, so any
+// change to what an extractor reports shows up in review
+#[test]
+fn fixtures() {
+    insta::glob!("fixtures/*.rs", |path| {
+        let code = fs::read_to_string(path).unwrap();
+        insta::assert_json_snapshot!(parse_to_json(&code).unwrap());
+    });
+}
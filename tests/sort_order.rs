@@ -0,0 +1,60 @@
+use serde_json::Value;
+use treesitter::{ASTConversionService, SortOrder};
+
+const CODE: &str = r#"
+fn zeta() {}
+macro_rules! alpha { () => {}; }
+fn alpha() {}
+fn beta() { alpha!(); let word = "b"; let count = 2; }
+"#;
+
+fn names(json: &Value, category: &str) -> Vec<String> {
+    json[category]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|item| match item.get("type") {
+            Some(kind) => format!(
+                "{}:{}",
+                kind.as_str().unwrap(),
+                item["name"].as_str().unwrap()
+            ),
+            None => item["name"].as_str().unwrap().to_string(),
+        })
+        .collect()
+}
+
+fn generate(order: SortOrder) -> Value {
+    let mut service = ASTConversionService::new(CODE.to_string()).unwrap();
+    service.set_sort_order(order);
+    service.generate_json()
+}
+
+#[test]
+fn categories_are_sorted_by_the_chosen_order() {
+    let source = generate(SortOrder::Source);
+    assert_eq!(names(&source, "functions"), ["zeta", "alpha", "beta"]);
+    assert_eq!(
+        names(&source, "macros"),
+        ["definition:alpha", "invocation:alpha"]
+    );
+
+    let by_name = generate(SortOrder::Name);
+    assert_eq!(names(&by_name, "functions"), ["alpha", "beta", "zeta"]);
+
+    let by_kind = generate(SortOrder::Kind);
+    let literal_types: Vec<&Value> = by_kind["literals"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|literal| &literal["type"])
+        .collect();
+    assert_eq!(literal_types, ["integer", "string"]);
+}
+
+#[test]
+fn sorted_output_is_identical_across_runs() {
+    for order in [SortOrder::Source, SortOrder::Name, SortOrder::Kind] {
+        assert_eq!(generate(order).to_string(), generate(order).to_string());
+    }
+}
@@ -0,0 +1,35 @@
+use treesitter::ASTConversionService;
+
+#[test]
+fn every_top_level_item_gets_a_row_with_its_visibility() {
+    let code = r#"
+pub const MAX: u32 = 8;
+pub trait Shape {}
+type Id = u64;
+static mut COUNT: u32 = 0;
+fn main() {}
+"#;
+    let service = ASTConversionService::new(code.to_string()).unwrap();
+    let rows: Vec<(String, String, Option<String>)> = service
+        .to_symbol_table()
+        .into_iter()
+        .map(|row| (row.kind, row.name, row.visibility))
+        .collect();
+    let row = |kind: &str, name: &str, visibility: &str| {
+        (
+            kind.to_string(),
+            name.to_string(),
+            Some(visibility.to_string()),
+        )
+    };
+    assert_eq!(
+        rows,
+        vec![
+            row("constant", "MAX", "pub"),
+            row("trait", "Shape", "pub"),
+            row("type_alias", "Id", "private"),
+            row("static", "COUNT", "private"),
+            row("function", "main", "private"),
+        ]
+    );
+}
@@ -0,0 +1,20 @@
+use std::time::Duration;
+
+use treesitter::{ASTConversionService, ConversionError, Language};
+
+#[test]
+fn parsing_past_the_timeout_is_reported() {
+    let code: String = (0..20_000)
+        .map(|i| format!("fn f{i}(x: u32) -> u32 {{ if x > {i} {{ x }} else {{ 0 }} }}\n"))
+        .collect();
+    let timeout = Some(Duration::from_micros(1));
+    match ASTConversionService::with_timeout(code, Language::Rust, timeout) {
+        Err(ConversionError::Timeout { input_len, .. }) => assert!(input_len > 0),
+        Err(err) => panic!("expected a timeout, got {}", err),
+        Ok(_) => panic!("expected a timeout"),
+    }
+
+    let small = "fn main() {}".to_string();
+    let timeout = Some(Duration::from_secs(10));
+    assert!(ASTConversionService::with_timeout(small, Language::Rust, timeout).is_ok());
+}
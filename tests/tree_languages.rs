@@ -0,0 +1,35 @@
+use treesitter::{ASTConversionService, Kind, Language, Thing};
+
+fn kinds_and_names(code: &str, language: Language) -> Vec<(Kind, Option<String>)> {
+    fn walk(thing: Thing, nodes: &mut Vec<(Kind, Option<String>)>) {
+        nodes.push((thing.kind, thing.name));
+        for child in thing.children {
+            walk(child, nodes);
+        }
+    }
+    let service = ASTConversionService::with_language(code.to_string(), language).unwrap();
+    let mut nodes = Vec::new();
+    walk(service.generate_ast_with_relations(), &mut nodes);
+    nodes
+}
+
+#[test]
+fn python_files_build_a_tree() {
+    let code = "class A:\n    def m(self):\n        return f()\n";
+    let nodes = kinds_and_names(code, Language::Python);
+    let named = |kind: Kind, name: &str| (kind, Some(name.to_string()));
+    assert!(nodes.contains(&named(Kind::Struct, "A")));
+    assert!(nodes.contains(&named(Kind::Function, "m")));
+    assert!(nodes.contains(&named(Kind::FunctionCall, "f")));
+}
+
+#[test]
+fn javascript_files_build_a_tree() {
+    let code = "class C { m() {} }\nfunction f() { g(); }\n";
+    let nodes = kinds_and_names(code, Language::JavaScript);
+    let named = |kind: Kind, name: &str| (kind, Some(name.to_string()));
+    assert!(nodes.contains(&named(Kind::Struct, "C")));
+    assert!(nodes.contains(&named(Kind::Function, "m")));
+    assert!(nodes.contains(&named(Kind::Function, "f")));
+    assert!(nodes.contains(&named(Kind::FunctionCall, "g")));
+}
@@ -0,0 +1,20 @@
+use treesitter::{ASTConversionService, Kind, TreeOptions};
+
+#[test]
+fn truncation_marks_the_nearest_kept_node() {
+    let code = "fn f() { let x = 1; }";
+    let service = ASTConversionService::new(code.to_string()).unwrap();
+    // The function's block, which maps to no kind, is where the builder stops
+    let options = TreeOptions {
+        max_depth: 2,
+        ..TreeOptions::default()
+    };
+    let root = service.generate_tree(&options);
+
+    let file = &root.children[0];
+    assert!(!file.truncated);
+    let function = &file.children[0];
+    assert_eq!(function.kind, Kind::Function);
+    assert!(function.truncated);
+    assert!(function.children.is_empty());
+}
@@ -0,0 +1,29 @@
+use treesitter::parse_to_json;
+
+#[test]
+fn multi_byte_source_is_extracted_without_panicking() {
+    let code = r#"
+const GREETING: &str = "héllo 👋 wörld";
+
+fn naïve_größe(ß: u32) -> &'static str {
+    let emoji = "🦀🦀";
+    emoji
+}
+
+struct Ünïcode {
+    名前: String,
+}
+"#;
+    let json = parse_to_json(code).unwrap();
+
+    assert_eq!(json["constants"][0]["name"], "GREETING");
+    assert_eq!(json["constants"][0]["value"], "\"héllo 👋 wörld\"");
+
+    let function = &json["functions"][0];
+    assert_eq!(function["name"], "naïve_größe");
+    assert_eq!(function["parameters"][0]["name"], "ß");
+    assert!(function["body"].as_str().unwrap().contains("\"🦀🦀\""));
+
+    assert_eq!(json["structs"][0]["name"], "Ünïcode");
+    assert_eq!(json["structs"][0]["fields"][0]["name"], "名前");
+}
@@ -0,0 +1,87 @@
+use treesitter::{ASTConversionService, VisibilityFilter};
+
+fn public_only(code: &str) -> ASTConversionService {
+    let mut service = ASTConversionService::new(code.to_string()).unwrap();
+    service.set_visibility_filter(VisibilityFilter::Public);
+    service
+}
+
+#[test]
+fn public_only_applies_inside_modules() {
+    let service = public_only("pub mod m { fn inner_private() {} pub fn inner_public() {} }");
+    let modules = service.modules_and_impls();
+    let names: Vec<&str> = modules[0]["children"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|item| item["name"].as_str().unwrap())
+        .collect();
+    assert_eq!(names, ["inner_public"]);
+}
+
+#[test]
+fn public_only_applies_to_the_symbol_table() {
+    let service = public_only("fn private() {}\npub struct S;\nconst C: u8 = 0;\npub type T = u8;");
+    let names: Vec<String> = service
+        .to_symbol_table()
+        .into_iter()
+        .map(|row| row.name)
+        .collect();
+    assert_eq!(names, ["S", "T"]);
+}
+
+#[test]
+fn public_only_applies_to_impl_children_and_nested_outputs() {
+    let code = r#"
+mod private_m {
+    pub fn x() {}
+}
+pub fn api() {
+    helper();
+}
+pub fn helper() {}
+fn top() {
+    helper();
+}
+pub struct S;
+struct Hidden;
+impl S {
+    fn secret(&self) {}
+    pub fn open(&self) {}
+}
+impl Clone for S {
+    fn clone(&self) -> S {
+        S
+    }
+}
+#[derive(Debug)]
+struct D;
+#[test]
+fn checks() {}
+"#;
+    let service = public_only(code);
+    let names = |items: &[serde_json::Value]| -> Vec<String> {
+        items
+            .iter()
+            .map(|item| item["name"].as_str().unwrap().to_string())
+            .collect()
+    };
+
+    let containers = service.modules_and_impls();
+    assert_eq!(names(&containers), ["S", "S"]);
+    let children = containers[0]["children"].as_array().unwrap();
+    assert_eq!(names(children), ["open"]);
+    // Trait impl methods are as public as the trait
+    let children = containers[1]["children"].as_array().unwrap();
+    assert_eq!(names(children), ["clone"]);
+
+    assert_eq!(names(&service.nested_items()), ["api", "helper", "S"]);
+    assert_eq!(
+        service.call_edges(),
+        [("api".to_string(), "helper".to_string())]
+    );
+    assert!(service.tests().is_empty());
+    assert_eq!(service.edges().len(), 1);
+    assert!(!service.to_dot().contains("Hidden"));
+    assert!(!service.to_dot().contains("\"D\""));
+}